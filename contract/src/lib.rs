@@ -1,9 +1,30 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short,
-    Address, Env, Symbol, Vec, Map, String, BytesN,
+    contract, contracterror, contractimpl, contracttype, symbol_short, token, xdr::ToXdr,
+    Address, BytesN, Env, IntoVal, InvokeError, Symbol, Val, Vec, String,
 };
 
+mod metadata;
+mod migration;
+mod nft;
+
+pub use metadata::{write_metadata_json, MAX_TEXT_LEN};
+pub use migration::SCHEMA_VERSION;
+pub use nft::NonFungibleToken;
+
+/// Lifecycle state of an access key. `Expired` and `Suspended` are also derived
+/// from ledger time and account state by `key_status`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyStatus {
+    Active,
+    Expired,
+    Revoked,
+    Suspended,
+    Burned,
+    /// Issued but not usable yet
+    PendingActivation,
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -12,8 +33,37 @@ pub struct AccessKey {
     pub owner: Address,
     pub content_id: String,
     pub expires_at: u64,
-    pub is_active: bool,
+    pub status: KeyStatus,
     pub transferable: bool,
+    /// Account the key is lent to, which holds access instead of the owner until `user_expires`
+    pub user: Option<Address>,
+    pub user_expires: u64,
+}
+
+/// Who may mint keys for a content item
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MintPolicy {
+    /// Only the creator issues keys
+    CreatorOnly,
+    /// Anyone may buy a key at the content price
+    PublicSale,
+    /// Allowlisted recipients may buy a key at the content price
+    Allowlist,
+    /// Only the contract admin issues keys
+    AdminOnly,
+}
+
+/// How long keys for a content item last, measured from the mint time
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DurationPolicy {
+    /// Every key lasts exactly this many seconds
+    Fixed(u64),
+    /// The minter picks a duration between the minimum and maximum seconds
+    Bounded(u64, u64),
+    /// Keys never expire; minters must pass a duration of 0
+    Perpetual,
 }
 
 #[contracttype]
@@ -24,72 +74,1932 @@ pub struct ContentMetadata {
     pub creator: Address,
     pub price: i128,
     pub max_keys: u32,
+    pub mint_policy: MintPolicy,
+    pub duration_policy: DurationPolicy,
+    pub retired: bool,
+    pub royalty_bps: u32,
+    /// Length of one renewal period in seconds (0 disables renewals)
+    pub renewal_period: u64,
+    pub renewal_price: i128,
+}
+
+/// A secondary-market offer to sell a key
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Listing {
+    pub key_id: u64,
+    pub seller: Address,
+    pub price: i128,
+    pub token: Address,
+    /// Creator royalty in force when the key was listed
+    pub royalty_bps: u32,
+}
+
+/// Operational roles. The admin implicitly holds every role except `MinterOperator`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// May grant and revoke any role
+    SuperAdmin,
+    /// May freeze and unfreeze accounts
+    ComplianceOfficer,
+    /// May suspend and reinstate content
+    ContentModerator,
+    /// May withdraw the platform fees held by the contract
+    Treasurer,
+    /// May issue keys on behalf of the given creator
+    MinterOperator(Address),
+}
+
+/// Permission for `spender` to move a single key until `expiration_ledger`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Approval {
+    pub spender: Address,
+    pub expiration_ledger: u32,
+}
+
+/// A creator's off-chain promise of one key, redeemed with `mint_with_voucher`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Voucher {
+    pub content_id: String,
+    /// Account the key is for, or `None` if anyone may redeem the voucher
+    pub recipient: Option<Address>,
+    /// Key lifetime in seconds from redemption, as in `mint`
+    pub duration: u64,
+    pub transferable: bool,
+    /// Unique per creator; each nonce can be redeemed once
+    pub nonce: u64,
+    /// Last ledger timestamp at which the voucher can be redeemed
+    pub deadline: u64,
+}
+
+/// Storage TTL thresholds and extensions, in ledgers
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+    pub instance_threshold: u32,
+    pub instance_extend_to: u32,
+    pub persistent_threshold: u32,
+    pub persistent_extend_to: u32,
+    /// How long key entries are kept alive after the key expires, at least
+    /// `persistent_extend_to` while the key is still listed for its owner
+    pub expired_key_grace: u32,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    AdminNotSet = 2,
+    KeyNotFound = 3,
+    NotTransferable = 4,
+    KeyInactive = 5,
+    KeyExpired = 6,
+    AccountFrozen = 7,
+    ContentNotFound = 8,
+    PaymentTokenNotSet = 9,
+    InvalidPrice = 10,
+    SupplyExhausted = 11,
+    Unauthorized = 12,
+    NotAllowlisted = 13,
+    NoPendingAdmin = 14,
+    ContentAlreadyExists = 15,
+    ContentRetired = 16,
+    ListingNotFound = 17,
+    InvalidRoyalty = 18,
+    RenewalNotAvailable = 19,
+    InvalidPeriods = 20,
+    InvalidDuration = 21,
+    InvalidTtlConfig = 22,
+    UnknownSchema = 23,
+    ContractPaused = 24,
+    ContentSuspended = 25,
+    KeyRevoked = 26,
+    KeyBurned = 27,
+    KeyRented = 28,
+    InvalidExpiration = 29,
+    InvalidUri = 30,
+    VoucherExpired = 31,
+    NonceUsed = 32,
+    VoucherKeyNotSet = 33,
+    ArithmeticOverflow = 34,
+    RedemptionFailed = 35,
+    InvalidFee = 36,
+}
+
+#[contracttype]
+pub enum DataKey {
+    AccessKey(u64),
+    OwnerKeyAt(Address, u32),
+    OwnerKeyIndex(u64),
+    ContentMeta(String),
+    KeyCounter,
+    FrozenAccount(Address),
+    OwnedCount(Address),
+    OwnerContentKeys(Address, String),
+    ContentKeyCount(String),
+    ContentKeyAt(String, u32),
+    MintedCount(String),
+    Allowlist(String, Address),
+    Listing(u64),
+    ContentSuspended(String),
+    ContentSuspendedBy(String),
+    Role(Role, Address),
+    RedemptionHook(String),
+    RenterContentKeys(Address, String),
+    Approval(u64),
+    OperatorApproval(Address, Address),
+    BaseUri(String),
+    VoucherKey(Address),
+    VoucherNonce(Address, u64),
+}
+
+// Events
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyMintedEvent {
+    pub key_id: u64,
+    pub owner: Address,
+    pub content_id: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyTransferredEvent {
+    pub key_id: u64,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyPurchasedEvent {
+    pub key_id: u64,
+    pub buyer: Address,
+    pub creator: Address,
+    pub token: Address,
+    pub price: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyRenewedEvent {
+    pub key_id: u64,
+    pub owner: Address,
+    pub periods: u32,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyRevokedEvent {
+    pub key_id: u64,
+    pub owner: Address,
+    pub content_id: String,
+    pub reason_code: u32,
+    pub revoked_by: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyBurnedEvent {
+    pub key_id: u64,
+    pub owner: Address,
+    pub content_id: String,
+    pub redeemed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyUserUpdatedEvent {
+    pub key_id: u64,
+    pub user: Option<Address>,
+    pub expires: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApprovalEvent {
+    pub key_id: u64,
+    pub owner: Address,
+    pub spender: Address,
+    pub expiration_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApprovalForAllEvent {
+    pub owner: Address,
+    pub operator: Address,
+    pub approved: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyListedEvent {
+    pub key_id: u64,
+    pub seller: Address,
+    pub price: i128,
+    pub token: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingCancelledEvent {
+    pub key_id: u64,
+    pub seller: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeySoldEvent {
+    pub key_id: u64,
+    pub seller: Address,
+    pub buyer: Address,
+    pub price: i128,
+    pub royalty: i128,
+    pub token: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SupplyExhaustedEvent {
+    pub content_id: String,
+    pub max_keys: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractInitializedEvent {
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminProposedEvent {
+    pub admin: Address,
+    pub proposed: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminTransferCancelledEvent {
+    pub admin: Address,
+    pub cancelled: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeesWithdrawnEvent {
+    pub treasurer: Address,
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminChangedEvent {
+    pub previous: Address,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContentRegisteredEvent {
+    pub content_id: String,
+    pub creator: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContentUpdatedEvent {
+    pub content_id: String,
+    pub price: i128,
+    pub max_keys: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContentOwnershipTransferredEvent {
+    pub content_id: String,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContentRetiredEvent {
+    pub content_id: String,
+    pub creator: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContentSuspendedEvent {
+    pub content_id: String,
+    pub suspended: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractPausedEvent {
+    pub paused: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleChangedEvent {
+    pub role: Role,
+    pub account: Address,
+    pub sender: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractUpgradedEvent {
+    pub wasm_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemaMigratedEvent {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccountFrozenEvent {
+    pub account: Address,
+    pub frozen: bool,
 }
 
-#[contracttype]
-pub enum DataKey {
-    AccessKey(u64),
-    UserKeys(Address),
-    ContentMeta(String),
-    KeyCounter,
-    FrozenAccount(Address),
-    Balance(Address),
-}
+const ADMIN: Symbol = symbol_short!("ADMIN");
+const PENDING_ADMIN: Symbol = symbol_short!("PENDADM");
+const KEY_COUNTER: Symbol = symbol_short!("KEYCNT");
+const PAYMENT_TOKEN: Symbol = symbol_short!("PAYTOKEN");
+const TOTAL_KEYS: Symbol = symbol_short!("TOTKEYS");
+const TOTAL_CONTENTS: Symbol = symbol_short!("TOTCNTS");
+const TTL_CONFIG: Symbol = symbol_short!("TTLCFG");
+const SCHEMA: Symbol = symbol_short!("SCHEMA");
+const PAUSED: Symbol = symbol_short!("PAUSED");
+const PLATFORM_FEE: Symbol = symbol_short!("FEEBPS");
+const MIGRATION_CURSOR: Symbol = symbol_short!("MIGRCUR");
+
+const LEDGER_SECONDS: u64 = 5;
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Maximum number of key IDs returned by one page of `keys_of` / `keys_for_content`
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// `ContentMetadata.max_keys` value meaning the content has no supply cap
+pub const UNLIMITED_SUPPLY: u32 = 0;
+
+/// `AccessKey.expires_at` value of keys minted under `DurationPolicy::Perpetual`
+pub const NEVER_EXPIRES: u64 = u64::MAX;
+
+/// Basis points in a whole sale price
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Highest royalty a creator can charge on secondary sales (25%)
+pub const MAX_ROYALTY_BPS: u32 = 2_500;
+
+/// Highest platform fee taken from key sales and renewals (10%)
+pub const MAX_PLATFORM_FEE_BPS: u32 = 1_000;
+
+/// Longest accepted content base URI, in bytes
+pub const MAX_URI_LEN: u32 = 200;
+
+#[contract]
+pub struct DigitalAccessKeysContract;
+
+#[contractimpl]
+impl DigitalAccessKeysContract {
+    
+    /// Initialize the contract
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&ADMIN) {
+            return Err(Error::AlreadyInitialized);
+        }
+        
+        // The admin must consent, so nobody can hand a fresh deployment to an
+        // address they don't control
+        admin.require_auth();
+
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().set(&KEY_COUNTER, &0u64);
+        env.storage().instance().set(&SCHEMA, &SCHEMA_VERSION);
+        Self::extend_instance(&env);
+        
+        env.events().publish(
+            (symbol_short!("init"), &admin),
+            ContractInitializedEvent {
+                admin: admin.clone(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Replace the contract code (admin only). Call `migrate` afterwards if the
+    /// new code uses a newer storage schema.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        let admin = Self::require_admin(&env)?;
+        admin.require_auth();
+        
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        
+        env.events().publish(
+            (symbol_short!("upgrade"),),
+            ContractUpgradedEvent {
+                wasm_hash: new_wasm_hash,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Get the storage schema version of the contract data
+    pub fn schema_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&SCHEMA)
+            .unwrap_or(SCHEMA_VERSION)
+    }
+
+    /// Convert stored data to the current schema (admin only). Keys are converted
+    /// in ID order, up to `limit` IDs per call, together with the content they
+    /// belong to; content without keys cannot be enumerated and is passed in
+    /// `content_ids`. The schema version is only raised once every key has been
+    /// converted, see `pending_migration`.
+    pub fn migrate(env: Env, content_ids: Vec<String>, limit: u32) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let admin = Self::require_admin(&env)?;
+        admin.require_auth();
+        
+        for content_id in content_ids.iter() {
+            Self::migrate_content(&env, &content_id)?;
+        }
+        
+        let from_version = Self::schema_version(env.clone());
+        if from_version == SCHEMA_VERSION {
+            return Ok(());
+        }
+        
+        let last_id: u64 = env.storage().instance().get(&KEY_COUNTER).unwrap_or(0);
+        let mut next_id: u64 = env.storage().instance().get(&MIGRATION_CURSOR).unwrap_or(1);
+        let end_id = next_id.saturating_add(limit as u64);
+        
+        while next_id <= last_id && next_id < end_id {
+            // IDs of keys deleted by earlier schemas are simply skipped
+            if let Some(val) = env.storage().persistent().get::<_, Val>(&DataKey::AccessKey(next_id)) {
+                Self::migrate_key(&env, next_id, &val)?;
+            }
+            next_id += 1;
+        }
+        
+        if next_id <= last_id {
+            env.storage().instance().set(&MIGRATION_CURSOR, &next_id);
+            return Ok(());
+        }
+        
+        env.storage().instance().remove(&MIGRATION_CURSOR);
+        env.storage().instance().set(&SCHEMA, &SCHEMA_VERSION);
+        
+        env.events().publish(
+            (symbol_short!("migrated"),),
+            SchemaMigratedEvent {
+                from_version,
+                to_version: SCHEMA_VERSION,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Get the number of key IDs `migrate` still has to convert
+    pub fn pending_migration(env: Env) -> u64 {
+        if Self::schema_version(env.clone()) == SCHEMA_VERSION {
+            return 0;
+        }
+        
+        let last_id: u64 = env.storage().instance().get(&KEY_COUNTER).unwrap_or(0);
+        let next_id: u64 = env.storage().instance().get(&MIGRATION_CURSOR).unwrap_or(1);
+        (last_id + 1).saturating_sub(next_id)
+    }
+
+    /// Get the current admin
+    pub fn admin(env: Env) -> Result<Address, Error> {
+        Self::require_admin(&env)
+    }
+
+    /// Get the admin proposed by `propose_admin`, if any
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&PENDING_ADMIN)
+    }
+
+    /// Propose a new admin (admin only). Takes effect once the new admin calls `accept_admin`.
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let admin = Self::require_admin(&env)?;
+        admin.require_auth();
+        
+        env.storage().instance().set(&PENDING_ADMIN, &new_admin);
+        
+        env.events().publish(
+            (symbol_short!("adm_prop"), &new_admin),
+            AdminProposedEvent {
+                admin,
+                proposed: new_admin.clone(),
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Withdraw a pending admin proposal (admin only)
+    pub fn cancel_admin_transfer(env: Env) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let admin = Self::require_admin(&env)?;
+        admin.require_auth();
+        
+        let proposed: Address = env.storage()
+            .instance()
+            .get(&PENDING_ADMIN)
+            .ok_or(Error::NoPendingAdmin)?;
+        env.storage().instance().remove(&PENDING_ADMIN);
+        
+        env.events().publish(
+            (symbol_short!("adm_cncl"), &proposed),
+            AdminTransferCancelledEvent {
+                admin,
+                cancelled: proposed.clone(),
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Accept a pending admin proposal (proposed admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let previous = Self::require_admin(&env)?;
+        let new_admin: Address = env.storage()
+            .instance()
+            .get(&PENDING_ADMIN)
+            .ok_or(Error::NoPendingAdmin)?;
+        
+        new_admin.require_auth();
+        
+        env.storage().instance().set(&ADMIN, &new_admin);
+        env.storage().instance().remove(&PENDING_ADMIN);
+        
+        env.events().publish(
+            (symbol_short!("adm_chg"), &new_admin),
+            AdminChangedEvent {
+                previous,
+                admin: new_admin.clone(),
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Set the token contract used to pay for access keys (admin only)
+    pub fn set_payment_token(env: Env, token: Address) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let admin = Self::require_admin(&env)?;
+        admin.require_auth();
+
+        env.storage().instance().set(&PAYMENT_TOKEN, &token);
+        Ok(())
+    }
+
+    /// Get the token contract used to pay for access keys
+    pub fn payment_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&PAYMENT_TOKEN)
+    }
+
+    /// Set the share of key sales and renewals kept by the contract for
+    /// treasurers to withdraw, in basis points (admin only)
+    pub fn set_platform_fee(env: Env, fee_bps: u32) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let admin = Self::require_admin(&env)?;
+        admin.require_auth();
+        
+        if fee_bps > MAX_PLATFORM_FEE_BPS {
+            return Err(Error::InvalidFee);
+        }
+        
+        env.storage().instance().set(&PLATFORM_FEE, &fee_bps);
+        Ok(())
+    }
+
+    /// Get the platform fee in basis points
+    pub fn platform_fee(env: Env) -> u32 {
+        env.storage().instance().get(&PLATFORM_FEE).unwrap_or(0)
+    }
+
+    /// Set the storage TTL thresholds and extensions (admin only)
+    pub fn set_ttl_config(env: Env, config: TtlConfig) -> Result<(), Error> {
+        let admin = Self::require_admin(&env)?;
+        admin.require_auth();
+        
+        if config.instance_threshold > config.instance_extend_to
+            || config.persistent_threshold > config.persistent_extend_to
+        {
+            return Err(Error::InvalidTtlConfig);
+        }
+        
+        env.storage().instance().set(&TTL_CONFIG, &config);
+        Self::extend_instance(&env);
+        Ok(())
+    }
+
+    /// Get the storage TTL thresholds and extensions in use
+    pub fn ttl_config(env: Env) -> TtlConfig {
+        env.storage().instance().get(&TTL_CONFIG).unwrap_or(TtlConfig {
+            instance_threshold: 29 * DAY_IN_LEDGERS,
+            instance_extend_to: 30 * DAY_IN_LEDGERS,
+            persistent_threshold: 29 * DAY_IN_LEDGERS,
+            persistent_extend_to: 30 * DAY_IN_LEDGERS,
+            expired_key_grace: 7 * DAY_IN_LEDGERS,
+        })
+    }
+
+    /// Extend the storage TTL of a key and its ownership index (callable by anyone).
+    /// Keys are kept alive until their expiry plus a grace period, and never for
+    /// less than the owner's index entries that list them.
+    pub fn bump_key(env: Env, key_id: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let key = Self::load_key(&env, key_id)?;
+        Self::extend_key_ttl(&env, &key);
+        Ok(())
+    }
+
+    /// Mint a new access key for digital content according to its minting policy.
+    /// Sale policies charge the content price to `minter`; creator gifts are free.
+    /// The key expires `duration` seconds from now, which must fit the content's duration policy.
+    pub fn mint(
+        env: Env,
+        minter: Address,
+        to: Address,
+        content_id: String,
+        duration: u64,
+        transferable: bool,
+    ) -> Result<u64, Error> {
+        Self::extend_instance(&env);
+        
+        // Require authentication
+        minter.require_auth();
+        
+        Self::require_not_paused(&env)?;
+        
+        // Check if accounts are frozen
+        Self::require_not_frozen(&env, &minter)?;
+        Self::require_not_frozen(&env, &to)?;
+        
+        // Content must be registered and still on sale
+        let metadata = Self::load_content(&env, &content_id)?;
+        if metadata.retired {
+            return Err(Error::ContentRetired);
+        }
+        Self::require_content_not_suspended(&env, &content_id)?;
+        
+        let paid = Self::check_mint_policy(&env, &minter, &to, &content_id, &metadata)?;
+        Self::issue_key(&env, &minter, &to, &content_id, &metadata, duration, transferable, paid)
+    }
+
+    /// Register the ed25519 public key that signs the creator's vouchers (creator only)
+    pub fn set_voucher_key(env: Env, creator: Address, public_key: BytesN<32>) {
+        Self::extend_instance(&env);
+        
+        creator.require_auth();
+        Self::save(&env, &DataKey::VoucherKey(creator), &public_key);
+    }
+
+    /// Get the public key that signs a creator's vouchers, if registered
+    pub fn voucher_key(env: Env, creator: Address) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::VoucherKey(creator))
+    }
+
+    /// Check whether a creator's voucher nonce has been redeemed
+    pub fn is_nonce_used(env: Env, creator: Address, nonce: u64) -> bool {
+        env.storage().persistent().has(&DataKey::VoucherNonce(creator, nonce))
+    }
+
+    /// Mint a free key to `redeemer` from a voucher signed by the content creator's
+    /// voucher key. The signature covers the XDR of `(contract address, voucher)`;
+    /// invalid signatures abort the call.
+    pub fn mint_with_voucher(
+        env: Env,
+        redeemer: Address,
+        voucher: Voucher,
+        signature: BytesN<64>,
+    ) -> Result<u64, Error> {
+        Self::extend_instance(&env);
+        
+        redeemer.require_auth();
+        
+        Self::require_not_paused(&env)?;
+        Self::require_not_frozen(&env, &redeemer)?;
+        
+        let metadata = Self::load_content(&env, &voucher.content_id)?;
+        if metadata.retired {
+            return Err(Error::ContentRetired);
+        }
+        Self::require_content_not_suspended(&env, &voucher.content_id)?;
+        Self::require_not_frozen(&env, &metadata.creator)?;
+        
+        // Vouchers mint as the creator, so admin-only content rejects them
+        let creator = metadata.creator.clone();
+        let paid = Self::check_mint_policy(&env, &creator, &redeemer, &voucher.content_id, &metadata)?;
+        
+        if voucher.recipient.as_ref().is_some_and(|recipient| *recipient != redeemer) {
+            return Err(Error::Unauthorized);
+        }
+        if env.ledger().timestamp() > voucher.deadline {
+            return Err(Error::VoucherExpired);
+        }
+        
+        let nonce_key = DataKey::VoucherNonce(metadata.creator.clone(), voucher.nonce);
+        if env.storage().persistent().has(&nonce_key) {
+            return Err(Error::NonceUsed);
+        }
+        
+        let public_key = Self::voucher_key(env.clone(), metadata.creator.clone())
+            .ok_or(Error::VoucherKeyNotSet)?;
+        let message = (env.current_contract_address(), voucher.clone()).to_xdr(&env);
+        env.crypto().ed25519_verify(&public_key, &message, &signature);
+        
+        Self::save(&env, &nonce_key, &true);
+        
+        Self::issue_key(
+            &env,
+            &creator,
+            &redeemer,
+            &voucher.content_id,
+            &metadata,
+            voucher.duration,
+            voucher.transferable,
+            paid,
+        )
+    }
+
+    /// Allow or disallow `operator` to move all keys of `owner` (owner only)
+    pub fn set_approval_for_all(env: Env, owner: Address, operator: Address, approved: bool) {
+        Self::extend_instance(&env);
+        
+        owner.require_auth();
+        
+        let entry = DataKey::OperatorApproval(owner.clone(), operator.clone());
+        if approved {
+            Self::save(&env, &entry, &true);
+        } else {
+            env.storage().persistent().remove(&entry);
+        }
+        
+        env.events().publish(
+            (symbol_short!("appr_all"), &owner),
+            ApprovalForAllEvent {
+                owner: owner.clone(),
+                operator,
+                approved,
+            }
+        );
+    }
+
+    /// Get the account approved to move a key, if the approval is still live
+    pub fn get_approved(env: Env, key_id: u64) -> Option<Address> {
+        let approval: Approval = env.storage()
+            .persistent()
+            .get(&DataKey::Approval(key_id))?;
+        
+        if env.ledger().sequence() > approval.expiration_ledger {
+            return None;
+        }
+        
+        Some(approval.spender)
+    }
+
+    /// Check if `operator` may move all keys of `owner`
+    pub fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OperatorApproval(owner, operator))
+            .unwrap_or(false)
+    }
+
+    /// List a transferable key for sale at `price` in `token` (owner only)
+    pub fn list_key(env: Env, key_id: u64, price: i128, token: Address) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let key = Self::load_key(&env, key_id)?;
+        key.owner.require_auth();
+        
+        Self::require_transferable(&env, &key)?;
+        
+        if price <= 0 {
+            return Err(Error::InvalidPrice);
+        }
+        
+        // Fix the royalty now so the seller knows their cut
+        let metadata = Self::load_content(&env, &key.content_id)?;
+        Self::bps_amount(price, metadata.royalty_bps)?;
+        
+        let listing = Listing {
+            key_id,
+            seller: key.owner.clone(),
+            price,
+            token,
+            royalty_bps: metadata.royalty_bps,
+        };
+        Self::save(&env, &DataKey::Listing(key_id), &listing);
+        
+        env.events().publish(
+            (symbol_short!("listed"), &key.owner),
+            KeyListedEvent {
+                key_id,
+                seller: listing.seller,
+                price,
+                token: listing.token,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Remove a key from sale (seller only)
+    pub fn cancel_listing(env: Env, key_id: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let listing: Listing = env.storage()
+            .persistent()
+            .get(&DataKey::Listing(key_id))
+            .ok_or(Error::ListingNotFound)?;
+        
+        listing.seller.require_auth();
+        
+        env.storage().persistent().remove(&DataKey::Listing(key_id));
+        
+        env.events().publish(
+            (symbol_short!("unlisted"), &listing.seller),
+            ListingCancelledEvent {
+                key_id,
+                seller: listing.seller.clone(),
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Buy a listed key, paying the seller and transferring the key atomically
+    pub fn buy_listing(env: Env, buyer: Address, key_id: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        buyer.require_auth();
+        
+        Self::require_not_paused(&env)?;
+        
+        let listing = Self::get_listing(env.clone(), key_id).ok_or(Error::ListingNotFound)?;
+        let key = Self::load_key(&env, key_id)?;
+        
+        // Split the payment between the creator and the seller at the listed royalty
+        let creator = Self::load_content(&env, &key.content_id)?.creator;
+        let royalty = Self::bps_amount(listing.price, listing.royalty_bps)?;
+        let token_client = token::Client::new(&env, &listing.token);
+        if royalty > 0 {
+            token_client.transfer(&buyer, &creator, &royalty);
+        }
+        token_client.transfer(&buyer, &listing.seller, &(listing.price - royalty));
+        
+        Self::transfer_key(&env, key, &buyer)?;
+        
+        env.events().publish(
+            (symbol_short!("sold"), &listing.seller, &buyer),
+            KeySoldEvent {
+                key_id,
+                seller: listing.seller.clone(),
+                buyer: buyer.clone(),
+                price: listing.price,
+                royalty,
+                token: listing.token,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Get the active listing for a key. Listings of keys that expired, became
+    /// inactive, changed owner or whose seller is frozen are not returned.
+    pub fn get_listing(env: Env, key_id: u64) -> Option<Listing> {
+        let listing: Listing = env.storage()
+            .persistent()
+            .get(&DataKey::Listing(key_id))?;
+        let key = Self::get_key(env.clone(), key_id)?;
+        
+        // A pause halts sales without invalidating listings
+        if key.owner != listing.seller || Self::require_key_transferable(&env, &key).is_err() {
+            return None;
+        }
+        
+        Some(listing)
+    }
+
+    /// Get the number of keys owned by an address, including expired and inactive ones
+    pub fn owned_count(env: Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OwnedCount(owner))
+            .unwrap_or(0)
+    }
+
+    /// Get the number of keys owned by an address that are valid right now
+    pub fn active_count(env: Env, owner: Address) -> u32 {
+        let mut count = 0;
+        for index in 0..Self::owned_count(env.clone(), owner.clone()) {
+            let key_id: u64 = env.storage()
+                .persistent()
+                .get(&DataKey::OwnerKeyAt(owner.clone(), index))
+                .unwrap();
+            if Self::is_key_valid(env.clone(), key_id) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Get the number of valid keys an address holds for content
+    pub fn balance_of(env: Env, owner: Address, content_id: String) -> u32 {
+        let mut count = 0;
+        for key_id in Self::owner_content_keys(&env, &owner, &content_id).iter() {
+            if Self::is_key_valid(env.clone(), key_id) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Check whether `user` may access content right now. Returns the valid key
+    /// granting access, or `None` if there is none, the account is frozen or the
+    /// content is suspended. Lent keys grant access to their user, not their owner,
+    /// and stop doing so while the owner is frozen, matching `key_status`.
+    pub fn has_access(env: Env, user: Address, content_id: String) -> Option<AccessKey> {
+        if Self::is_frozen(env.clone(), user.clone())
+            || Self::is_content_suspended(env.clone(), content_id.clone())
+        {
+            return None;
+        }
+        
+        Self::owner_content_keys(&env, &user, &content_id)
+            .iter()
+            .filter_map(|key_id| Self::get_key(env.clone(), key_id))
+            .find(|key| Self::is_valid(&env, key) && !Self::is_rented(&env, key))
+            .or_else(|| {
+                Self::renter_content_keys(&env, &user, &content_id)
+                    .iter()
+                    .filter_map(|key_id| Self::get_key(env.clone(), key_id))
+                    .find(|key| {
+                        Self::is_valid(&env, key)
+                            && Self::is_rented(&env, key)
+                            && key.user.as_ref() == Some(&user)
+                            && !Self::is_frozen(env.clone(), key.owner.clone())
+                    })
+            })
+    }
+
+    /// Lend a key to `user` until `until` (owner only), or cancel the current
+    /// lease with `None`. The owner keeps the key but cannot move it while it is lent.
+    pub fn set_user(env: Env, key_id: u64, user: Option<Address>, until: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut key = Self::load_key(&env, key_id)?;
+        key.owner.require_auth();
+        
+        Self::require_not_paused(&env)?;
+        Self::require_content_not_suspended(&env, &key.content_id)?;
+        Self::require_not_frozen(&env, &key.owner)?;
+        
+        if let Some(user) = &user {
+            Self::require_not_frozen(&env, user)?;
+            
+            if !key.transferable {
+                return Err(Error::NotTransferable);
+            }
+            if key.status != KeyStatus::Active {
+                return Err(Error::KeyInactive);
+            }
+            if until <= env.ledger().timestamp() || until > key.expires_at {
+                return Err(Error::InvalidDuration);
+            }
+        }
+        
+        if let Some(previous) = key.user.take() {
+            Self::remove_key_from_renter(&env, &previous, &key.content_id, key_id);
+        }
+        if let Some(user) = &user {
+            Self::add_key_to_renter(&env, user, &key.content_id, key_id);
+        }
+        key.user_expires = if user.is_some() { until } else { 0 };
+        key.user = user;
+        Self::save_key(&env, &key);
+        
+        env.events().publish(
+            (symbol_short!("set_user"), key_id),
+            KeyUserUpdatedEvent {
+                key_id,
+                user: key.user.clone(),
+                expires: key.user_expires,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Get the account a key is currently lent to, if the lease is live
+    pub fn user_of(env: Env, key_id: u64) -> Option<Address> {
+        let key = Self::get_key(env.clone(), key_id)?;
+        if !Self::is_rented(&env, &key) {
+            return None;
+        }
+        key.user
+    }
+
+    /// Freeze or unfreeze an account (compliance officers only)
+    pub fn freeze_account(env: Env, caller: Address, account: Address, freeze: bool) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        Self::require_role(&env, &caller, &Role::ComplianceOfficer)?;
+        
+        if freeze {
+            Self::save(&env, &DataKey::FrozenAccount(account.clone()), &true);
+        } else {
+            env.storage().persistent().remove(&DataKey::FrozenAccount(account.clone()));
+        }
+        
+        // Emit event
+        env.events().publish(
+            (symbol_short!("freeze"), &account),
+            AccountFrozenEvent {
+                account: account.clone(),
+                frozen: freeze,
+            }
+        );
+
+        Ok(())
+    }
+
+    /// Grant a role to an account. Super admins may grant any role except
+    /// `MinterOperator`, which only the creator it acts for may grant.
+    pub fn grant_role(env: Env, caller: Address, role: Role, account: Address) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        Self::require_role_manager(&env, &caller, &role)?;
+        Self::save(&env, &DataKey::Role(role.clone(), account.clone()), &true);
+        
+        env.events().publish(
+            (symbol_short!("grant"), &account),
+            RoleChangedEvent {
+                role,
+                account: account.clone(),
+                sender: caller,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Revoke a role from an account, with the same permissions as `grant_role`
+    pub fn revoke_role(env: Env, caller: Address, role: Role, account: Address) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        Self::require_role_manager(&env, &caller, &role)?;
+        env.storage().persistent().remove(&DataKey::Role(role.clone(), account.clone()));
+        
+        env.events().publish(
+            (symbol_short!("revoke"), &account),
+            RoleChangedEvent {
+                role,
+                account: account.clone(),
+                sender: caller,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Check if an account holds a role, either explicitly or as the admin
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        // Minting for a creator always needs the creator's consent
+        let implicit = !matches!(role, Role::MinterOperator(_));
+        if implicit && Self::require_admin(&env).map(|admin| admin == account).unwrap_or(false) {
+            return true;
+        }
+        
+        env.storage()
+            .persistent()
+            .get(&DataKey::Role(role, account))
+            .unwrap_or(false)
+    }
+
+    /// Withdraw platform fees held by the contract (treasurers only)
+    pub fn withdraw(
+        env: Env,
+        caller: Address,
+        token: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        Self::require_role(&env, &caller, &Role::Treasurer)?;
+        
+        if amount <= 0 {
+            return Err(Error::InvalidPrice);
+        }
+        
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+        
+        env.events().publish(
+            (symbol_short!("withdraw"), &to),
+            FeesWithdrawnEvent {
+                treasurer: caller,
+                token,
+                to: to.clone(),
+                amount,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Pause minting, transfers, renewals and the marketplace (admin only)
+    pub fn pause(env: Env) -> Result<(), Error> {
+        Self::set_paused(&env, true)
+    }
+
+    /// Resume normal operation after `pause` (admin only)
+    pub fn unpause(env: Env) -> Result<(), Error> {
+        Self::set_paused(&env, false)
+    }
+
+    /// Check if the contract is paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&PAUSED).unwrap_or(false)
+    }
+
+    /// Check if an account is frozen
+    pub fn is_frozen(env: Env, account: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::FrozenAccount(account))
+            .unwrap_or(false)
+    }
+
+    /// Get access key details
+    pub fn get_key(env: Env, key_id: u64) -> Option<AccessKey> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AccessKey(key_id))
+    }
+
+    /// Get up to `limit` keys owned by an address, starting at position `cursor`.
+    /// Pass `cursor + returned length` to fetch the next page. Positions of other keys
+    /// can change when a key leaves the owner, so pages may shift between calls.
+    pub fn keys_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<u64> {
+        let count = Self::owned_count(env.clone(), owner.clone());
+        Self::page(&env, cursor, limit, count, |index| DataKey::OwnerKeyAt(owner.clone(), index))
+    }
+
+    /// Deprecated: use `keys_of`. Returns the first `MAX_PAGE_SIZE` keys owned by a user.
+    pub fn get_user_keys(env: Env, user: Address) -> Vec<u64> {
+        Self::keys_of(env, user, 0, MAX_PAGE_SIZE)
+    }
+
+    /// Get up to `limit` keys minted for content, starting at position `cursor`
+    pub fn keys_for_content(env: Env, content_id: String, cursor: u32, limit: u32) -> Vec<u64> {
+        let count: u32 = env.storage()
+            .persistent()
+            .get(&DataKey::ContentKeyCount(content_id.clone()))
+            .unwrap_or(0);
+        Self::page(&env, cursor, limit, count, |index| DataKey::ContentKeyAt(content_id.clone(), index))
+    }
+
+    /// Get the total number of access keys in existence
+    pub fn total_keys(env: Env) -> u64 {
+        env.storage().instance().get(&TOTAL_KEYS).unwrap_or(0)
+    }
+
+    /// Get the total number of registered content items
+    pub fn total_contents(env: Env) -> u64 {
+        env.storage().instance().get(&TOTAL_CONTENTS).unwrap_or(0)
+    }
+
+    /// Check if a key is valid and active
+    pub fn is_key_valid(env: Env, key_id: u64) -> bool {
+        Self::get_key(env.clone(), key_id)
+            .map(|key| Self::is_valid(&env, &key))
+            .unwrap_or(false)
+    }
+
+    /// Get the effective status of a key right now. Keys that ran out or belong to
+    /// frozen accounts or suspended content report `Expired` and `Suspended` even
+    /// before their stored status is updated.
+    pub fn key_status(env: Env, key_id: u64) -> Result<KeyStatus, Error> {
+        let key = Self::load_key(&env, key_id)?;
+        
+        if key.status != KeyStatus::Active {
+            return Ok(key.status);
+        }
+        if Self::is_frozen(env.clone(), key.owner.clone())
+            || Self::is_content_suspended(env.clone(), key.content_id.clone())
+        {
+            return Ok(KeyStatus::Suspended);
+        }
+        if env.ledger().timestamp() > key.expires_at {
+            return Ok(KeyStatus::Expired);
+        }
+        
+        Ok(KeyStatus::Active)
+    }
+
+    /// Deactivate an expired key (can be called by anyone)
+    pub fn deactivate_expired_key(env: Env, key_id: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut key = Self::load_key(&env, key_id)?;
+        
+        let current_time = env.ledger().timestamp();
+        
+        if current_time > key.expires_at && key.status == KeyStatus::Active {
+            key.status = KeyStatus::Expired;
+            Self::save_key(&env, &key);
+        }
+
+        Ok(())
+    }
+
+    /// Permanently revoke a key (content creator or admin), e.g. when it was issued
+    /// by mistake, refunded or obtained by fraud. Like burned keys, revoked keys
+    /// leave their owner's balances, lose listings, approvals and rentals, and are
+    /// kept as a `Revoked` record.
+    pub fn revoke_key(env: Env, caller: Address, key_id: u64, reason_code: u32) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut key = Self::load_key(&env, key_id)?;
+        let metadata = Self::load_content(&env, &key.content_id)?;
+        
+        caller.require_auth();
+        if caller != metadata.creator && caller != Self::require_admin(&env)? {
+            return Err(Error::Unauthorized);
+        }
+        
+        match key.status {
+            KeyStatus::Revoked => return Err(Error::KeyRevoked),
+            KeyStatus::Burned => return Err(Error::KeyBurned),
+            _ => {}
+        }
+        
+        Self::retire_key(&env, &mut key, KeyStatus::Revoked);
+        
+        env.events().publish(
+            (symbol_short!("revoked"), &key.owner),
+            KeyRevokedEvent {
+                key_id,
+                owner: key.owner.clone(),
+                content_id: key.content_id.clone(),
+                reason_code,
+                revoked_by: caller,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Destroy a key (owner only). The key leaves its owner's balances and is kept
+    /// as a `Burned` record; burning a valid key calls the content's redemption hook.
+    pub fn burn(env: Env, key_id: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut key = Self::load_key(&env, key_id)?;
+        key.owner.require_auth();
+        
+        Self::require_not_paused(&env)?;
+        Self::require_not_frozen(&env, &key.owner)?;
+        
+        match key.status {
+            KeyStatus::Revoked => return Err(Error::KeyRevoked),
+            KeyStatus::Burned => return Err(Error::KeyBurned),
+            _ => {}
+        }
+        if Self::is_rented(&env, &key) {
+            return Err(Error::KeyRented);
+        }
+        
+        let redeemed = Self::is_valid(&env, &key);
+        Self::retire_key(&env, &mut key, KeyStatus::Burned);
+        
+        let hook: Option<Address> = env.storage()
+            .persistent()
+            .get(&DataKey::RedemptionHook(key.content_id.clone()));
+        if let (true, Some(hook)) = (redeemed, hook) {
+            let result = env.try_invoke_contract::<(), InvokeError>(
+                &hook,
+                &Symbol::new(&env, "on_redeem"),
+                (key_id, key.owner.clone(), key.content_id.clone()).into_val(&env),
+            );
+            
+            // Returning an error rolls the burn back, so the owner keeps a key they could not redeem
+            if !matches!(result, Ok(Ok(()))) {
+                return Err(Error::RedemptionFailed);
+            }
+        }
+        
+        env.events().publish(
+            (symbol_short!("burn"), &key.owner),
+            KeyBurnedEvent {
+                key_id,
+                owner: key.owner.clone(),
+                content_id: key.content_id.clone(),
+                redeemed,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Renew a key for a number of periods (owner only), charging the content's
+    /// per-period price. Extends from the later of now and the current expiry and
+    /// reactivates keys deactivated after expiring.
+    pub fn renew(env: Env, key_id: u64, periods: u32) -> Result<u64, Error> {
+        Self::extend_instance(&env);
+        
+        let mut key = Self::load_key(&env, key_id)?;
+        key.owner.require_auth();
+        
+        match key.status {
+            KeyStatus::Revoked => return Err(Error::KeyRevoked),
+            KeyStatus::Burned => return Err(Error::KeyBurned),
+            KeyStatus::Active | KeyStatus::Expired => {}
+            _ => return Err(Error::KeyInactive),
+        }
+        
+        Self::require_not_paused(&env)?;
+        Self::require_content_not_suspended(&env, &key.content_id)?;
+        Self::require_not_frozen(&env, &key.owner)?;
+        
+        if periods == 0 {
+            return Err(Error::InvalidPeriods);
+        }
+        
+        let metadata = Self::load_content(&env, &key.content_id)?;
+        if metadata.retired {
+            return Err(Error::ContentRetired);
+        }
+        if metadata.renewal_period == 0 {
+            return Err(Error::RenewalNotAvailable);
+        }
+        
+        let price = metadata.renewal_price
+            .checked_mul(periods as i128)
+            .ok_or(Error::ArithmeticOverflow)?;
+        if price > 0 {
+            Self::collect_payment(&env, &key.owner, &metadata.creator, price)?;
+        }
+        
+        let start = key.expires_at.max(env.ledger().timestamp());
+        key.expires_at = metadata.renewal_period
+            .checked_mul(periods as u64)
+            .and_then(|extension| start.checked_add(extension))
+            .ok_or(Error::InvalidDuration)?;
+        
+        key.status = KeyStatus::Active;
+        
+        Self::save_key(&env, &key);
+        
+        env.events().publish(
+            (symbol_short!("renewed"), &key.owner),
+            KeyRenewedEvent {
+                key_id,
+                owner: key.owner.clone(),
+                periods,
+                expires_at: key.expires_at,
+            }
+        );
+        
+        Ok(key.expires_at)
+    }
+
+    /// Register new content. Each `content_id` can only be claimed once.
+    #[allow(clippy::too_many_arguments)]
+    pub fn register_content(
+        env: Env,
+        content_id: String,
+        title: String,
+        description: String,
+        creator: Address,
+        price: i128,
+        max_keys: u32,
+        duration_policy: DurationPolicy,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        creator.require_auth();
+        
+        if env.storage().persistent().has(&DataKey::ContentMeta(content_id.clone())) {
+            return Err(Error::ContentAlreadyExists);
+        }
+        
+        if price < 0 {
+            return Err(Error::InvalidPrice);
+        }
+        
+        Self::validate_duration_policy(&duration_policy)?;
+        
+        let metadata = ContentMetadata {
+            title,
+            description,
+            creator: creator.clone(),
+            price,
+            max_keys,
+            mint_policy: MintPolicy::PublicSale,
+            duration_policy,
+            retired: false,
+            royalty_bps: 0,
+            renewal_period: 0,
+            renewal_price: 0,
+        };
+        
+        Self::save(&env, &DataKey::ContentMeta(content_id.clone()), &metadata);
+        Self::bump_counter(&env, &TOTAL_CONTENTS);
+        
+        env.events().publish(
+            (symbol_short!("cnt_reg"), &creator),
+            ContentRegisteredEvent {
+                content_id,
+                creator: creator.clone(),
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Update content details (creator only)
+    pub fn update_content(
+        env: Env,
+        content_id: String,
+        title: String,
+        description: String,
+        price: i128,
+        max_keys: u32,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        if metadata.retired {
+            return Err(Error::ContentRetired);
+        }
+        
+        if price < 0 {
+            return Err(Error::InvalidPrice);
+        }
+        
+        metadata.title = title;
+        metadata.description = description;
+        metadata.price = price;
+        metadata.max_keys = max_keys;
+        Self::save(&env, &DataKey::ContentMeta(content_id.clone()), &metadata);
+        
+        env.events().publish(
+            (symbol_short!("cnt_upd"), &metadata.creator),
+            ContentUpdatedEvent {
+                content_id,
+                price,
+                max_keys,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Hand content over to a new creator (creator only)
+    pub fn transfer_content_ownership(
+        env: Env,
+        content_id: String,
+        new_creator: Address,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        let from = metadata.creator.clone();
+        metadata.creator = new_creator.clone();
+        Self::save(&env, &DataKey::ContentMeta(content_id.clone()), &metadata);
+        
+        env.events().publish(
+            (symbol_short!("cnt_own"), &from, &new_creator),
+            ContentOwnershipTransferredEvent {
+                content_id,
+                from: from.clone(),
+                to: new_creator.clone(),
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Retire content (creator only). No new keys can be minted, but existing keys
+    /// stay valid and the `content_id` can never be registered again.
+    pub fn retire_content(env: Env, content_id: String) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        if metadata.retired {
+            return Err(Error::ContentRetired);
+        }
+        
+        metadata.retired = true;
+        Self::save(&env, &DataKey::ContentMeta(content_id.clone()), &metadata);
+        
+        env.events().publish(
+            (symbol_short!("cnt_ret"), &metadata.creator),
+            ContentRetiredEvent {
+                content_id,
+                creator: metadata.creator.clone(),
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Suspend or reinstate content (creator or content moderators). Keys to
+    /// suspended content cannot be minted, moved or renewed and do not grant access.
+    /// Only moderators can lift a suspension a moderator applied.
+    pub fn suspend_content(
+        env: Env,
+        caller: Address,
+        content_id: String,
+        suspended: bool,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let metadata = Self::load_content(&env, &content_id)?;
+        let moderator = Self::has_role(env.clone(), Role::ContentModerator, caller.clone());
+        if caller != metadata.creator && !moderator {
+            return Err(Error::Unauthorized);
+        }
+        caller.require_auth();
+        
+        let key = DataKey::ContentSuspended(content_id.clone());
+        let by_key = DataKey::ContentSuspendedBy(content_id.clone());
+        let suspended_by: Option<Address> = env.storage().persistent().get(&by_key);
+        let by_creator = suspended_by.as_ref().map(|by| *by == metadata.creator).unwrap_or(false);
+        
+        if suspended {
+            // A moderator takes over a creator's suspension, but not the other way around
+            if moderator || !Self::is_content_suspended(env.clone(), content_id.clone()) {
+                Self::save(&env, &by_key, &caller);
+            }
+            Self::save(&env, &key, &true);
+        } else {
+            // Suspensions recorded before `ContentSuspendedBy` count as a moderator's
+            if !moderator && !by_creator && env.storage().persistent().has(&key) {
+                return Err(Error::Unauthorized);
+            }
+            env.storage().persistent().remove(&key);
+            env.storage().persistent().remove(&by_key);
+        }
+        
+        env.events().publish(
+            (symbol_short!("suspend"), content_id.clone()),
+            ContentSuspendedEvent {
+                content_id,
+                suspended,
+            }
+        );
+        
+        Ok(())
+    }
+
+    /// Check if content is suspended
+    pub fn is_content_suspended(env: Env, content_id: String) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ContentSuspended(content_id))
+            .unwrap_or(false)
+    }
+
+    /// Get the account that suspended content, if it is suspended
+    pub fn suspended_by(env: Env, content_id: String) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ContentSuspendedBy(content_id))
+    }
+
+    /// Set the minting policy for content (creator only)
+    pub fn set_mint_policy(env: Env, content_id: String, policy: MintPolicy) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut metadata = Self::load_content(&env, &content_id)?;
+        
+        metadata.creator.require_auth();
+        
+        metadata.mint_policy = policy;
+        Self::save(&env, &DataKey::ContentMeta(content_id), &metadata);
+        Ok(())
+    }
+
+    /// Set the duration policy for future keys to content (creator only)
+    pub fn set_duration_policy(
+        env: Env,
+        content_id: String,
+        duration_policy: DurationPolicy,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        Self::validate_duration_policy(&duration_policy)?;
+        
+        metadata.duration_policy = duration_policy;
+        Self::save(&env, &DataKey::ContentMeta(content_id), &metadata);
+        Ok(())
+    }
+
+    /// Set the royalty paid to the creator on secondary sales, in basis points (creator only)
+    pub fn set_royalty(env: Env, content_id: String, royalty_bps: u32) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        if royalty_bps > MAX_ROYALTY_BPS {
+            return Err(Error::InvalidRoyalty);
+        }
+        
+        metadata.royalty_bps = royalty_bps;
+        Self::save(&env, &DataKey::ContentMeta(content_id), &metadata);
+        Ok(())
+    }
+
+    /// Set the base URI of content metadata (creator only). Key metadata lives at
+    /// the base URI followed by the key ID; an empty URI removes it.
+    pub fn set_base_uri(env: Env, content_id: String, base_uri: String) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        if base_uri.len() > MAX_URI_LEN {
+            return Err(Error::InvalidUri);
+        }
+        
+        if base_uri.is_empty() {
+            env.storage().persistent().remove(&DataKey::BaseUri(content_id));
+        } else {
+            Self::save(&env, &DataKey::BaseUri(content_id), &base_uri);
+        }
+        Ok(())
+    }
+
+    /// Get the base URI of content metadata, if any
+    pub fn base_uri(env: Env, content_id: String) -> Option<String> {
+        env.storage().persistent().get(&DataKey::BaseUri(content_id))
+    }
+
+    /// Set the contract called as `on_redeem(key_id, owner, content_id)` when a valid
+    /// key is burned, or `None` to remove it (creator only)
+    pub fn set_redemption_hook(env: Env, content_id: String, hook: Option<Address>) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        match hook {
+            Some(hook) => Self::save(&env, &DataKey::RedemptionHook(content_id), &hook),
+            None => env.storage().persistent().remove(&DataKey::RedemptionHook(content_id)),
+        }
+        Ok(())
+    }
+
+    /// Get the redemption hook of content, if any
+    pub fn redemption_hook(env: Env, content_id: String) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::RedemptionHook(content_id))
+    }
+
+    /// Set the renewal period length in seconds and the price per period (creator only).
+    /// A period of 0 disables renewals.
+    pub fn set_renewal_terms(
+        env: Env,
+        content_id: String,
+        renewal_period: u64,
+        renewal_price: i128,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let mut metadata = Self::load_content(&env, &content_id)?;
+        metadata.creator.require_auth();
+        
+        if renewal_price < 0 {
+            return Err(Error::InvalidPrice);
+        }
+        
+        metadata.renewal_period = renewal_period;
+        metadata.renewal_price = renewal_price;
+        Self::save(&env, &DataKey::ContentMeta(content_id), &metadata);
+        Ok(())
+    }
+
+    /// Get the royalty receiver and amount owed for a sale of a key to content (EIP-2981 style)
+    pub fn royalty_info(env: Env, content_id: String, sale_price: i128) -> Result<(Address, i128), Error> {
+        let metadata = Self::load_content(&env, &content_id)?;
+        
+        if sale_price < 0 {
+            return Err(Error::InvalidPrice);
+        }
+        
+        let royalty = Self::bps_amount(sale_price, metadata.royalty_bps)?;
+        Ok((metadata.creator, royalty))
+    }
+
+    /// Add or remove an account from the content allowlist (creator only)
+    pub fn set_allowlisted(
+        env: Env,
+        content_id: String,
+        account: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        let metadata = Self::load_content(&env, &content_id)?;
+        
+        metadata.creator.require_auth();
+        
+        let key = DataKey::Allowlist(content_id, account);
+        if allowed {
+            Self::save(&env, &key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Check if an account is on the content allowlist
+    pub fn is_allowlisted(env: Env, content_id: String, account: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Allowlist(content_id, account))
+            .unwrap_or(false)
+    }
 
-// Events
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct KeyMintedEvent {
-    pub key_id: u64,
-    pub owner: Address,
-    pub content_id: String,
-}
+    /// Get content metadata
+    pub fn get_content_metadata(env: Env, content_id: String) -> Option<ContentMetadata> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ContentMeta(content_id))
+    }
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct KeyTransferredEvent {
-    pub key_id: u64,
-    pub from: Address,
-    pub to: Address,
-}
+    /// Get the number of keys that can still be minted for content (`None` if unlimited)
+    pub fn remaining_supply(env: Env, content_id: String) -> Result<Option<u32>, Error> {
+        let metadata = Self::load_content(&env, &content_id)?;
+        
+        if metadata.max_keys == UNLIMITED_SUPPLY {
+            return Ok(None);
+        }
+        
+        let minted = Self::minted_count(&env, &content_id);
+        Ok(Some(metadata.max_keys.saturating_sub(minted)))
+    }
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AccountFrozenEvent {
-    pub account: Address,
-    pub frozen: bool,
-}
+    // Internal helper functions
+    fn extend_instance(env: &Env) {
+        let config = Self::ttl_config(env.clone());
+        env.storage()
+            .instance()
+            .extend_ttl(config.instance_threshold, config.instance_extend_to);
+    }
 
-const KEY_COUNTER: Symbol = symbol_short!("KEYCNT");
+    /// Write a persistent entry and extend its TTL
+    fn save<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+        let config = Self::ttl_config(env.clone());
+        env.storage().persistent().set(key, value);
+        env.storage()
+            .persistent()
+            .extend_ttl(key, config.persistent_threshold, config.persistent_extend_to);
+    }
 
-#[contract]
-pub struct DigitalAccessKeysContract;
+    /// Write an access key and extend the TTL of its entries to match its expiry
+    fn save_key(env: &Env, key: &AccessKey) {
+        env.storage().persistent().set(&DataKey::AccessKey(key.id), key);
+        Self::extend_key_ttl(env, key);
+    }
 
-#[contractimpl]
-impl DigitalAccessKeysContract {
+    fn extend_key_ttl(env: &Env, key: &AccessKey) {
+        // Burned and revoked keys have left their owner's indexes and may lapse
+        if matches!(key.status, KeyStatus::Burned | KeyStatus::Revoked) {
+            return;
+        }
+        
+        // An expired key stays listed for its owner, so it must not lapse before
+        // the index entries written alongside it
+        let config = Self::ttl_config(env.clone());
+        let ledgers_left = key.expires_at.saturating_sub(env.ledger().timestamp()) / LEDGER_SECONDS;
+        let extend_to = ledgers_left
+            .saturating_add(config.expired_key_grace as u64)
+            .max(config.persistent_extend_to as u64)
+            .min(env.storage().max_ttl() as u64) as u32;
+        
+        let storage = env.storage().persistent();
+        let extend = |entry: DataKey| {
+            if storage.has(&entry) {
+                storage.extend_ttl(&entry, extend_to, extend_to);
+            }
+        };
+        
+        // Everything `has_access` and the balances read must live as long as the key
+        Self::extend_listed_key(env, &key.owner, key.id, extend_to);
+        extend(DataKey::OwnedCount(key.owner.clone()));
+        extend(DataKey::OwnerContentKeys(key.owner.clone(), key.content_id.clone()));
+        extend(DataKey::FrozenAccount(key.owner.clone()));
+        extend(DataKey::ContentMeta(key.content_id.clone()));
+        extend(DataKey::ContentSuspended(key.content_id.clone()));
+        if let Some(user) = &key.user {
+            extend(DataKey::RenterContentKeys(user.clone(), key.content_id.clone()));
+            extend(DataKey::FrozenAccount(user.clone()));
+        }
+        
+        // Lookups read every key listed with this one, so none of them may lapse first
+        for key_id in Self::owner_content_keys(env, &key.owner, &key.content_id).iter() {
+            if key_id != key.id {
+                Self::extend_listed_key(env, &key.owner, key_id, extend_to);
+            }
+        }
+        if let Some(user) = &key.user {
+            for key_id in Self::renter_content_keys(env, user, &key.content_id).iter() {
+                let rented: Option<AccessKey> = storage.get(&DataKey::AccessKey(key_id));
+                if let Some(rented) = rented.filter(|rented| rented.id != key.id) {
+                    Self::extend_listed_key(env, &rented.owner, key_id, extend_to);
+                }
+            }
+        }
+    }
     
-    /// Initialize the contract
-    pub fn initialize(env: Env, admin: Address) {
-        env.storage().instance().set(&symbol_short!("ADMIN"), &admin);
-        env.storage().instance().set(&KEY_COUNTER, &0u64);
+    /// Extend a key record and its slot in the owner enumeration
+    fn extend_listed_key(env: &Env, owner: &Address, key_id: u64, extend_to: u32) {
+        let storage = env.storage().persistent();
+        let record = DataKey::AccessKey(key_id);
+        if !storage.has(&record) {
+            return;
+        }
+        storage.extend_ttl(&record, extend_to, extend_to);
+        
+        let index_key = DataKey::OwnerKeyIndex(key_id);
+        let index: Option<u32> = storage.get(&index_key);
+        if let Some(index) = index {
+            storage.extend_ttl(&index_key, extend_to, extend_to);
+            storage.extend_ttl(&DataKey::OwnerKeyAt(owner.clone(), index), extend_to, extend_to);
+        }
     }
 
-    /// Mint a new access key for digital content
-    pub fn mint(
-        env: Env,
-        to: Address,
-        content_id: String,
-        expires_at: u64,
+    /// Create a key after minting checks passed, charging `minter` if `paid`
+    #[allow(clippy::too_many_arguments)]
+    fn issue_key(
+        env: &Env,
+        minter: &Address,
+        to: &Address,
+        content_id: &String,
+        metadata: &ContentMetadata,
+        duration: u64,
         transferable: bool,
-    ) -> u64 {
-        // Require authentication
-        to.require_auth();
+        paid: bool,
+    ) -> Result<u64, Error> {
+        let expires_at = Self::compute_expiry(env, &metadata.duration_policy, duration)?;
         
-        // Check if account is frozen
-        Self::require_not_frozen(&env, &to);
+        // Enforce the supply cap
+        let minted = Self::minted_count(env, content_id);
+        if metadata.max_keys != UNLIMITED_SUPPLY && minted >= metadata.max_keys {
+            return Err(Error::SupplyExhausted);
+        }
+        Self::save(env, &DataKey::MintedCount(content_id.clone()), &(minted + 1));
         
         // Get next key ID
-        let key_id = Self::get_next_key_id(&env);
+        let key_id = Self::get_next_key_id(env);
         
         // Create the access key
         let access_key = AccessKey {
@@ -97,202 +2007,295 @@ impl DigitalAccessKeysContract {
             owner: to.clone(),
             content_id: content_id.clone(),
             expires_at,
-            is_active: true,
+            status: KeyStatus::Active,
+            user: None,
+            user_expires: 0,
             transferable,
         };
         
-        // Store the access key
-        env.storage().persistent().set(&DataKey::AccessKey(key_id), &access_key);
+        // Update user's and content's key lists
+        Self::add_key_to_user(env, to, content_id, key_id);
+        Self::add_key_to_content(env, content_id, key_id);
+        Self::bump_counter(env, &TOTAL_KEYS);
         
-        // Update user's key list
-        Self::add_key_to_user(&env, &to, key_id);
+        // Store the access key
+        Self::save_key(env, &access_key);
         
-        // Update balance
-        Self::increment_balance(&env, &to);
+        // Charge the buyer
+        if paid && metadata.price > 0 {
+            let token = Self::collect_payment(env, minter, &metadata.creator, metadata.price)?;
+            
+            env.events().publish(
+                (symbol_short!("purchase"), minter),
+                KeyPurchasedEvent {
+                    key_id,
+                    buyer: minter.clone(),
+                    creator: metadata.creator.clone(),
+                    token,
+                    price: metadata.price,
+                }
+            );
+        }
         
         // Emit event
         env.events().publish(
-            (symbol_short!("mint"), &to),
+            (symbol_short!("mint"), to),
             KeyMintedEvent {
                 key_id,
                 owner: to.clone(),
-                content_id,
+                content_id: content_id.clone(),
             }
         );
         
-        key_id
+        if metadata.max_keys != UNLIMITED_SUPPLY && minted + 1 == metadata.max_keys {
+            env.events().publish(
+                (Symbol::new(env, "supply_exhausted"), content_id.clone()),
+                SupplyExhaustedEvent {
+                    content_id: content_id.clone(),
+                    max_keys: metadata.max_keys,
+                }
+            );
+        }
+        
+        Ok(key_id)
+    }
+
+    fn minted_count(env: &Env, content_id: &String) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MintedCount(content_id.clone()))
+            .unwrap_or(0)
+    }
+
+    fn require_admin(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&ADMIN)
+            .ok_or(Error::AdminNotSet)
+    }
+
+    fn owner_content_keys(env: &Env, owner: &Address, content_id: &String) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OwnerContentKeys(owner.clone(), content_id.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn is_valid(env: &Env, key: &AccessKey) -> bool {
+        key.status == KeyStatus::Active && env.ledger().timestamp() <= key.expires_at
     }
 
-    /// Transfer an access key to another address
-    pub fn transfer(env: Env, key_id: u64, to: Address) {
-        // Get the access key
-        let mut key: AccessKey = env.storage()
+    fn is_rented(env: &Env, key: &AccessKey) -> bool {
+        key.user.is_some() && env.ledger().timestamp() <= key.user_expires
+    }
+
+    fn load_key(env: &Env, key_id: u64) -> Result<AccessKey, Error> {
+        env.storage()
             .persistent()
             .get(&DataKey::AccessKey(key_id))
-            .unwrap_or_else(|| panic!("Access key not found"));
-        
-        // Require owner authentication
-        key.owner.require_auth();
+            .ok_or(Error::KeyNotFound)
+    }
+
+    /// Check that a key can currently change hands
+    fn require_transferable(env: &Env, key: &AccessKey) -> Result<(), Error> {
+        Self::require_not_paused(env)?;
+        Self::require_key_transferable(env, key)
+    }
+
+    /// Check that a key could change hands once the contract is not paused
+    fn require_key_transferable(env: &Env, key: &AccessKey) -> Result<(), Error> {
+        Self::require_content_not_suspended(env, &key.content_id)?;
         
         // Check if key is transferable
         if !key.transferable {
-            panic!("This access key is not transferable");
+            return Err(Error::NotTransferable);
         }
         
         // Check if key is active and not expired
-        if !key.is_active {
-            panic!("Access key is not active");
+        if key.status == KeyStatus::Revoked {
+            return Err(Error::KeyRevoked);
+        }
+        if key.status == KeyStatus::Burned {
+            return Err(Error::KeyBurned);
+        }
+        if key.status != KeyStatus::Active {
+            return Err(Error::KeyInactive);
+        }
+        if Self::is_rented(env, key) {
+            return Err(Error::KeyRented);
         }
         
         let current_time = env.ledger().timestamp();
         if current_time > key.expires_at {
-            panic!("Access key has expired");
+            return Err(Error::KeyExpired);
         }
         
-        // Check if both accounts are not frozen
-        Self::require_not_frozen(&env, &key.owner);
-        Self::require_not_frozen(&env, &to);
+        Self::require_not_frozen(env, &key.owner)
+    }
+
+    /// Move a key to a new owner. Callers are responsible for authorization.
+    fn transfer_key(env: &Env, mut key: AccessKey, to: &Address) -> Result<(), Error> {
+        Self::require_transferable(env, &key)?;
+        Self::require_not_frozen(env, to)?;
         
+        let key_id = key.id;
         let from = key.owner.clone();
         
-        // Remove key from previous owner
-        Self::remove_key_from_user(&env, &from, key_id);
-        Self::decrement_balance(&env, &from);
+        // Move key between owners' key lists
+        Self::remove_key_from_user(env, &from, &key.content_id, key_id);
+        Self::add_key_to_user(env, to, &key.content_id, key_id);
         
-        // Add key to new owner
-        Self::add_key_to_user(&env, &to, key_id);
-        Self::increment_balance(&env, &to);
-        
-        // Update key owner
+        // Update key owner; any listing, approval or finished lease by the previous owner is void
+        if let Some(previous) = key.user.take() {
+            Self::remove_key_from_renter(env, &previous, &key.content_id, key_id);
+        }
+        key.user_expires = 0;
         key.owner = to.clone();
-        env.storage().persistent().set(&DataKey::AccessKey(key_id), &key);
+        Self::save_key(env, &key);
+        env.storage().persistent().remove(&DataKey::Listing(key_id));
+        env.storage().persistent().remove(&DataKey::Approval(key_id));
         
         // Emit event
         env.events().publish(
-            (symbol_short!("transfer"), &from, &to),
+            (symbol_short!("transfer"), &from, to),
             KeyTransferredEvent {
                 key_id,
                 from: from.clone(),
                 to: to.clone(),
             }
         );
+        
+        Ok(())
     }
 
-    /// Get the balance of access keys for an address
-    pub fn balance(env: Env, address: Address) -> i128 {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Balance(address))
-            .unwrap_or(0)
-    }
-
-    /// Freeze or unfreeze an account
-    pub fn freeze_account(env: Env, account: Address, freeze: bool) {
-        let admin: Address = env.storage()
-            .instance()
-            .get(&symbol_short!("ADMIN"))
-            .unwrap_or_else(|| panic!("Admin not set"));
-        
-        admin.require_auth();
-        
-        if freeze {
-            env.storage().persistent().set(&DataKey::FrozenAccount(account.clone()), &true);
-        } else {
-            env.storage().persistent().remove(&DataKey::FrozenAccount(account.clone()));
+    /// Cap the royalty of content configured under the old limit
+    fn migrate_content(env: &Env, content_id: &String) -> Result<(), Error> {
+        let mut metadata = Self::load_content(env, content_id)?;
+        if metadata.royalty_bps <= MAX_ROYALTY_BPS {
+            return Ok(());
         }
         
-        // Emit event
-        env.events().publish(
-            (symbol_short!("freeze"), &account),
-            AccountFrozenEvent {
-                account: account.clone(),
-                frozen: freeze,
-            }
-        );
+        // Royalties used to be allowed up to 100%
+        metadata.royalty_bps = MAX_ROYALTY_BPS;
+        Self::save(env, &DataKey::ContentMeta(content_id.clone()), &metadata);
+        Ok(())
     }
 
-    /// Check if an account is frozen
-    pub fn is_frozen(env: Env, account: Address) -> bool {
-        env.storage()
-            .persistent()
-            .get(&DataKey::FrozenAccount(account))
-            .unwrap_or(false)
+    /// Convert a stored key, its content and its listing to the current layout
+    fn migrate_key(env: &Env, key_id: u64, val: &Val) -> Result<(), Error> {
+        let key = migration::decode_key(env, val).ok_or(Error::UnknownSchema)?;
+        Self::migrate_content(env, &key.content_id)?;
+        Self::save_key(env, &key);
+        
+        let listing_entry = DataKey::Listing(key_id);
+        if let Some(val) = env.storage().persistent().get::<_, Val>(&listing_entry) {
+            let royalty_bps = Self::load_content(env, &key.content_id)?.royalty_bps;
+            let listing = migration::decode_listing(env, &val, royalty_bps)
+                .ok_or(Error::UnknownSchema)?;
+            Self::save(env, &listing_entry, &listing);
+        }
+        Ok(())
     }
 
-    /// Get access key details
-    pub fn get_key(env: Env, key_id: u64) -> Option<AccessKey> {
+    fn load_content(env: &Env, content_id: &String) -> Result<ContentMetadata, Error> {
         env.storage()
             .persistent()
-            .get(&DataKey::AccessKey(key_id))
+            .get(&DataKey::ContentMeta(content_id.clone()))
+            .ok_or(Error::ContentNotFound)
     }
 
-    /// Get all keys owned by an address
-    pub fn get_user_keys(env: Env, user: Address) -> Vec<u64> {
-        env.storage()
-            .persistent()
-            .get(&DataKey::UserKeys(user))
-            .unwrap_or(Vec::new(&env))
+    /// Returns whether the minter has to pay the content price
+    fn check_mint_policy(
+        env: &Env,
+        minter: &Address,
+        to: &Address,
+        content_id: &String,
+        metadata: &ContentMetadata,
+    ) -> Result<bool, Error> {
+        match metadata.mint_policy {
+            MintPolicy::AdminOnly => {
+                if *minter != Self::require_admin(env)? {
+                    return Err(Error::Unauthorized);
+                }
+                Ok(false)
+            }
+            // Creators and their operators may always gift keys outside of admin-only issuance
+            _ if *minter == metadata.creator
+                || Self::has_role(env.clone(), Role::MinterOperator(metadata.creator.clone()), minter.clone()) =>
+            {
+                Ok(false)
+            }
+            MintPolicy::CreatorOnly => Err(Error::Unauthorized),
+            MintPolicy::PublicSale => Ok(true),
+            MintPolicy::Allowlist => {
+                if !Self::is_allowlisted(env.clone(), content_id.clone(), to.clone()) {
+                    return Err(Error::NotAllowlisted);
+                }
+                Ok(true)
+            }
+        }
     }
 
-    /// Check if a key is valid and active
-    pub fn is_key_valid(env: Env, key_id: u64) -> bool {
-        if let Some(key) = Self::get_key(env.clone(), key_id) {
-            let current_time = env.ledger().timestamp();
-            key.is_active && current_time <= key.expires_at
-        } else {
-            false
+    fn validate_duration_policy(policy: &DurationPolicy) -> Result<(), Error> {
+        match *policy {
+            DurationPolicy::Fixed(0) => Err(Error::InvalidDuration),
+            DurationPolicy::Bounded(min, max) if min == 0 || min > max => Err(Error::InvalidDuration),
+            _ => Ok(()),
         }
     }
 
-    /// Deactivate an expired key (can be called by anyone)
-    pub fn deactivate_expired_key(env: Env, key_id: u64) {
-        let mut key: AccessKey = env.storage()
-            .persistent()
-            .get(&DataKey::AccessKey(key_id))
-            .unwrap_or_else(|| panic!("Access key not found"));
+    /// Compute the expiry of a key minted now for `duration` seconds
+    fn compute_expiry(env: &Env, policy: &DurationPolicy, duration: u64) -> Result<u64, Error> {
+        let allowed = match *policy {
+            DurationPolicy::Fixed(seconds) => duration == seconds,
+            DurationPolicy::Bounded(min, max) => (min..=max).contains(&duration),
+            DurationPolicy::Perpetual => duration == 0,
+        };
         
-        let current_time = env.ledger().timestamp();
+        if !allowed {
+            return Err(Error::InvalidDuration);
+        }
         
-        if current_time > key.expires_at && key.is_active {
-            key.is_active = false;
-            env.storage().persistent().set(&DataKey::AccessKey(key_id), &key);
-            
-            // Decrease user balance
-            Self::decrement_balance(&env, &key.owner);
+        if *policy == DurationPolicy::Perpetual {
+            return Ok(NEVER_EXPIRES);
         }
+        
+        env.ledger()
+            .timestamp()
+            .checked_add(duration)
+            .ok_or(Error::InvalidDuration)
     }
 
-    /// Set content metadata
-    pub fn set_content_metadata(
-        env: Env,
-        content_id: String,
-        title: String,
-        description: String,
-        creator: Address,
-        price: i128,
-        max_keys: u32,
-    ) {
-        creator.require_auth();
-        
-        let metadata = ContentMetadata {
-            title,
-            description,
-            creator,
-            price,
-            max_keys,
-        };
-        
-        env.storage().persistent().set(&DataKey::ContentMeta(content_id), &metadata);
+    /// Share of `amount` worth `bps` basis points
+    fn bps_amount(amount: i128, bps: u32) -> Result<i128, Error> {
+        amount
+            .checked_mul(bps as i128)
+            .map(|amount| amount / BPS_DENOMINATOR as i128)
+            .ok_or(Error::ArithmeticOverflow)
     }
 
-    /// Get content metadata
-    pub fn get_content_metadata(env: Env, content_id: String) -> Option<ContentMetadata> {
-        env.storage()
-            .persistent()
-            .get(&DataKey::ContentMeta(content_id))
+    /// Pay `price` in the payment token from `buyer` to `creator`, keeping the
+    /// platform fee in the contract, and return the token
+    fn collect_payment(
+        env: &Env,
+        buyer: &Address,
+        creator: &Address,
+        price: i128,
+    ) -> Result<Address, Error> {
+        let token_address: Address = env.storage()
+            .instance()
+            .get(&PAYMENT_TOKEN)
+            .ok_or(Error::PaymentTokenNotSet)?;
+        
+        let fee = Self::bps_amount(price, Self::platform_fee(env.clone()))?;
+        let token_client = token::Client::new(env, &token_address);
+        if fee > 0 {
+            token_client.transfer(buyer, &env.current_contract_address(), &fee);
+        }
+        token_client.transfer(buyer, creator, &(price - fee));
+        Ok(token_address)
     }
 
-    // Internal helper functions
     fn get_next_key_id(env: &Env) -> u64 {
         let current_id: u64 = env.storage()
             .instance()
@@ -304,60 +2307,202 @@ impl DigitalAccessKeysContract {
         next_id
     }
 
-    fn add_key_to_user(env: &Env, user: &Address, key_id: u64) {
-        let mut user_keys: Vec<u64> = env.storage()
-            .persistent()
-            .get(&DataKey::UserKeys(user.clone()))
-            .unwrap_or(Vec::new(env));
+    fn bump_counter(env: &Env, counter: &Symbol) {
+        let current: u64 = env.storage().instance().get(counter).unwrap_or(0);
+        env.storage().instance().set(counter, &(current + 1));
+    }
+
+    fn page<F>(env: &Env, cursor: u32, limit: u32, count: u32, slot: F) -> Vec<u64>
+    where
+        F: Fn(u32) -> DataKey,
+    {
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+        let mut keys = Vec::new(env);
+        for index in cursor..end {
+            keys.push_back(env.storage().persistent().get(&slot(index)).unwrap());
+        }
+        keys
+    }
+
+    /// Append a key to the owner's enumeration. Keys are stored one slot per
+    /// position with a reverse index so that removal is a constant-time swap.
+    fn add_key_to_user(env: &Env, user: &Address, content_id: &String, key_id: u64) {
+        let count = Self::owned_count(env.clone(), user.clone());
+        Self::save(env, &DataKey::OwnerKeyAt(user.clone(), count), &key_id);
+        Self::save(env, &DataKey::OwnerKeyIndex(key_id), &count);
+        Self::save(env, &DataKey::OwnedCount(user.clone()), &(count + 1));
+        
+        let content_key = DataKey::OwnerContentKeys(user.clone(), content_id.clone());
+        let mut content_keys = Self::owner_content_keys(env, user, content_id);
+        content_keys.push_back(key_id);
+        Self::save(env, &content_key, &content_keys);
+    }
+
+    /// Take a key out of circulation: detach it from its owner and renter, drop its
+    /// listing and approval, and keep it as a record with the given status
+    fn retire_key(env: &Env, key: &mut AccessKey, status: KeyStatus) {
+        Self::remove_key_from_user(env, &key.owner, &key.content_id, key.id);
+        if let Some(previous) = key.user.take() {
+            Self::remove_key_from_renter(env, &previous, &key.content_id, key.id);
+        }
+        key.user_expires = 0;
+        env.storage().persistent().remove(&DataKey::Listing(key.id));
+        env.storage().persistent().remove(&DataKey::Approval(key.id));
         
-        user_keys.push_back(key_id);
-        env.storage().persistent().set(&DataKey::UserKeys(user.clone()), &user_keys);
+        let total: u64 = env.storage().instance().get(&TOTAL_KEYS).unwrap_or(0);
+        env.storage().instance().set(&TOTAL_KEYS, &total.saturating_sub(1));
+        
+        key.status = status;
+        env.storage().persistent().set(&DataKey::AccessKey(key.id), &*key);
     }
 
-    fn remove_key_from_user(env: &Env, user: &Address, key_id: u64) {
-        let mut user_keys: Vec<u64> = env.storage()
+    fn remove_key_from_user(env: &Env, user: &Address, content_id: &String, key_id: u64) {
+        let count = Self::owned_count(env.clone(), user.clone());
+        let index: u32 = env.storage()
             .persistent()
-            .get(&DataKey::UserKeys(user.clone()))
-            .unwrap_or(Vec::new(env));
+            .get(&DataKey::OwnerKeyIndex(key_id))
+            .unwrap();
+        let last = count - 1;
         
-        // Find and remove the key
-        let mut new_keys = Vec::new(env);
-        for i in 0..user_keys.len() {
-            if user_keys.get(i).unwrap() != key_id {
-                new_keys.push_back(user_keys.get(i).unwrap());
-            }
+        // Move the last key into the freed slot
+        let mut moved = None;
+        if index != last {
+            let last_key: u64 = env.storage()
+                .persistent()
+                .get(&DataKey::OwnerKeyAt(user.clone(), last))
+                .unwrap();
+            Self::save(env, &DataKey::OwnerKeyAt(user.clone(), index), &last_key);
+            Self::save(env, &DataKey::OwnerKeyIndex(last_key), &index);
+            moved = Some(last_key);
+        }
+        env.storage().persistent().remove(&DataKey::OwnerKeyAt(user.clone(), last));
+        env.storage().persistent().remove(&DataKey::OwnerKeyIndex(key_id));
+        Self::save(env, &DataKey::OwnedCount(user.clone()), &last);
+        
+        let content_key = DataKey::OwnerContentKeys(user.clone(), content_id.clone());
+        let content_keys = Self::owner_content_keys(env, user, content_id);
+        let new_content_keys = Self::without_key(env, &content_keys, key_id);
+        if new_content_keys.is_empty() {
+            env.storage().persistent().remove(&content_key);
+        } else {
+            Self::save(env, &content_key, &new_content_keys);
         }
         
-        env.storage().persistent().set(&DataKey::UserKeys(user.clone()), &new_keys);
+        // The rewritten entries must not outlive the keys they still point to
+        for remaining in [new_content_keys.first(), moved] {
+            if let Some(remaining) = remaining.and_then(|key_id| Self::get_key(env.clone(), key_id)) {
+                Self::extend_key_ttl(env, &remaining);
+            }
+        }
     }
 
-    fn increment_balance(env: &Env, address: &Address) {
-        let current_balance: i128 = env.storage()
+    fn renter_content_keys(env: &Env, user: &Address, content_id: &String) -> Vec<u64> {
+        env.storage()
             .persistent()
-            .get(&DataKey::Balance(address.clone()))
-            .unwrap_or(0);
-        
-        env.storage().persistent().set(&DataKey::Balance(address.clone()), &(current_balance + 1));
+            .get(&DataKey::RenterContentKeys(user.clone(), content_id.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn add_key_to_renter(env: &Env, user: &Address, content_id: &String, key_id: u64) {
+        let mut keys = Self::renter_content_keys(env, user, content_id);
+        keys.push_back(key_id);
+        Self::save(env, &DataKey::RenterContentKeys(user.clone(), content_id.clone()), &keys);
+    }
+
+    fn remove_key_from_renter(env: &Env, user: &Address, content_id: &String, key_id: u64) {
+        let renter_key = DataKey::RenterContentKeys(user.clone(), content_id.clone());
+        let keys = Self::without_key(env, &Self::renter_content_keys(env, user, content_id), key_id);
+        if keys.is_empty() {
+            env.storage().persistent().remove(&renter_key);
+        } else {
+            Self::save(env, &renter_key, &keys);
+        }
     }
 
-    fn decrement_balance(env: &Env, address: &Address) {
-        let current_balance: i128 = env.storage()
+    fn add_key_to_content(env: &Env, content_id: &String, key_id: u64) {
+        let count: u32 = env.storage()
             .persistent()
-            .get(&DataKey::Balance(address.clone()))
+            .get(&DataKey::ContentKeyCount(content_id.clone()))
             .unwrap_or(0);
+        Self::save(env, &DataKey::ContentKeyAt(content_id.clone(), count), &key_id);
+        Self::save(env, &DataKey::ContentKeyCount(content_id.clone()), &(count + 1));
+    }
+
+    fn without_key(env: &Env, keys: &Vec<u64>, key_id: u64) -> Vec<u64> {
+        let mut new_keys = Vec::new(env);
+        for id in keys.iter() {
+            if id != key_id {
+                new_keys.push_back(id);
+            }
+        }
+        new_keys
+    }
+
+    fn require_role(env: &Env, caller: &Address, role: &Role) -> Result<(), Error> {
+        caller.require_auth();
+        
+        if !Self::has_role(env.clone(), role.clone(), caller.clone()) {
+            return Err(Error::Unauthorized);
+        }
+        
+        Ok(())
+    }
+
+    fn require_role_manager(env: &Env, caller: &Address, role: &Role) -> Result<(), Error> {
+        if let Role::MinterOperator(creator) = role {
+            if creator != caller {
+                return Err(Error::Unauthorized);
+            }
+            
+            caller.require_auth();
+            return Ok(());
+        }
+        
+        Self::require_role(env, caller, &Role::SuperAdmin)
+    }
+
+    fn set_paused(env: &Env, paused: bool) -> Result<(), Error> {
+        Self::extend_instance(env);
+        
+        let admin = Self::require_admin(env)?;
+        admin.require_auth();
+        
+        env.storage().instance().set(&PAUSED, &paused);
+        
+        let topic = if paused { symbol_short!("paused") } else { symbol_short!("unpaused") };
+        env.events().publish((topic,), ContractPausedEvent { paused });
+        
+        Ok(())
+    }
+
+    fn require_not_paused(env: &Env) -> Result<(), Error> {
+        if Self::is_paused(env.clone()) {
+            return Err(Error::ContractPaused);
+        }
+        
+        Ok(())
+    }
+
+    fn require_content_not_suspended(env: &Env, content_id: &String) -> Result<(), Error> {
+        if Self::is_content_suspended(env.clone(), content_id.clone()) {
+            return Err(Error::ContentSuspended);
+        }
         
-        let new_balance = if current_balance > 0 { current_balance - 1 } else { 0 };
-        env.storage().persistent().set(&DataKey::Balance(address.clone()), &new_balance);
+        Ok(())
     }
 
-    fn require_not_frozen(env: &Env, address: &Address) {
+    fn require_not_frozen(env: &Env, address: &Address) -> Result<(), Error> {
         let is_frozen: bool = env.storage()
             .persistent()
             .get(&DataKey::FrozenAccount(address.clone()))
             .unwrap_or(false);
         
         if is_frozen {
-            panic!("Account is frozen");
+            return Err(Error::AccountFrozen);
         }
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+mod test;
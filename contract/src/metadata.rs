@@ -0,0 +1,97 @@
+//! Metadata links and the canonical metadata document of access keys.
+
+use core::fmt::{self, Write};
+
+use soroban_sdk::{Env, String};
+
+use crate::{AccessKey, ContentMetadata, MAX_URI_LEN, NEVER_EXPIRES};
+
+/// Longest text field `write_metadata_json` can render, in bytes
+pub const MAX_TEXT_LEN: usize = 1024;
+
+/// Compose the metadata URI of a key from its content's base URI and the key ID
+pub(crate) fn token_uri(env: &Env, base_uri: &String, key_id: u64) -> String {
+    let mut buf = [0u8; MAX_URI_LEN as usize + 20];
+    let len = base_uri.len() as usize;
+    base_uri.copy_into_slice(&mut buf[..len]);
+    let len = len + write_decimal(&mut buf[len..], key_id);
+    String::from_bytes(env, &buf[..len])
+}
+
+/// Render the metadata JSON served at a key's `token_uri`: the key's name,
+/// description and image plus content, expiry, transferability and status
+/// attributes. Expiry is a unix timestamp, or `"Never"` for perpetual keys.
+/// Fails if a text field is longer than `MAX_TEXT_LEN` bytes.
+pub fn write_metadata_json<W: Write>(
+    out: &mut W,
+    key: &AccessKey,
+    content: &ContentMetadata,
+    image: &str,
+) -> fmt::Result {
+    out.write_str("{\"name\":\"")?;
+    write_escaped(out, &content.title)?;
+    write!(out, " #{}\",\"description\":\"", key.id)?;
+    write_escaped(out, &content.description)?;
+    out.write_str("\",\"image\":\"")?;
+    write_escaped_str(out, image)?;
+    
+    out.write_str("\",\"attributes\":[{\"trait_type\":\"Content\",\"value\":\"")?;
+    write_escaped(out, &key.content_id)?;
+    if key.expires_at == NEVER_EXPIRES {
+        out.write_str("\"},{\"trait_type\":\"Expires\",\"value\":\"Never\"}")?;
+    } else {
+        write!(
+            out,
+            "\"}},{{\"display_type\":\"date\",\"trait_type\":\"Expires\",\"value\":{}}}",
+            key.expires_at
+        )?;
+    }
+    write!(
+        out,
+        ",{{\"trait_type\":\"Transferable\",\"value\":{}}},{{\"trait_type\":\"Status\",\"value\":\"{:?}\"}}]}}",
+        key.transferable, key.status
+    )
+}
+
+fn write_escaped<W: Write>(out: &mut W, text: &String) -> fmt::Result {
+    let mut buf = [0u8; MAX_TEXT_LEN];
+    let len = text.len() as usize;
+    if len > MAX_TEXT_LEN {
+        return Err(fmt::Error);
+    }
+    
+    text.copy_into_slice(&mut buf[..len]);
+    let text = core::str::from_utf8(&buf[..len]).map_err(|_| fmt::Error)?;
+    write_escaped_str(out, text)
+}
+
+fn write_escaped_str<W: Write>(out: &mut W, text: &str) -> fmt::Result {
+    for c in text.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+fn write_decimal(buf: &mut [u8], mut value: u64) -> usize {
+    let mut digits = [0u8; 20];
+    let mut count = 0;
+    loop {
+        digits[count] = b'0' + (value % 10) as u8;
+        count += 1;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    
+    for (i, digit) in digits[..count].iter().rev().enumerate() {
+        buf[i] = *digit;
+    }
+    count
+}
@@ -0,0 +1,76 @@
+//! Storage layouts written by schema version 2, the first version with
+//! `upgrade`, and their conversion to the current layout.
+
+use soroban_sdk::{contracttype, Address, Env, Map, String, Symbol, TryFromVal, Val};
+
+use crate::{AccessKey, KeyStatus, Listing, MAX_ROYALTY_BPS};
+
+/// Storage schema version written by this build of the contract
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// `AccessKey` as stored by schema version 2
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccessKeyV2 {
+    pub id: u64,
+    pub owner: Address,
+    pub content_id: String,
+    pub expires_at: u64,
+    pub is_active: bool,
+    pub transferable: bool,
+}
+
+impl From<AccessKeyV2> for AccessKey {
+    fn from(old: AccessKeyV2) -> Self {
+        AccessKey {
+            id: old.id,
+            owner: old.owner,
+            content_id: old.content_id,
+            expires_at: old.expires_at,
+            // Keys could only be deactivated once expired
+            status: if old.is_active { KeyStatus::Active } else { KeyStatus::Expired },
+            transferable: old.transferable,
+            user: None,
+            user_expires: 0,
+        }
+    }
+}
+
+/// `Listing` as stored by schema version 2, which charged the live royalty
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingV2 {
+    pub key_id: u64,
+    pub seller: Address,
+    pub price: i128,
+    pub token: Address,
+}
+
+/// Decode a stored access key written by schema version 2 or the current one.
+/// Decoding a struct with the wrong layout traps, so layouts are told apart by fields.
+pub fn decode_key(env: &Env, val: &Val) -> Option<AccessKey> {
+    let fields = Map::<Symbol, Val>::try_from_val(env, val).ok()?;
+
+    if fields.contains_key(Symbol::new(env, "is_active")) {
+        return AccessKeyV2::try_from_val(env, val).ok().map(AccessKey::from);
+    }
+
+    AccessKey::try_from_val(env, val).ok()
+}
+
+/// Decode a stored listing, pinning version 2 listings to the content's current royalty
+pub fn decode_listing(env: &Env, val: &Val, royalty_bps: u32) -> Option<Listing> {
+    let fields = Map::<Symbol, Val>::try_from_val(env, val).ok()?;
+
+    if fields.contains_key(Symbol::new(env, "royalty_bps")) {
+        return Listing::try_from_val(env, val).ok();
+    }
+
+    ListingV2::try_from_val(env, val).ok().map(|old| Listing {
+        key_id: old.key_id,
+        seller: old.seller,
+        price: old.price,
+        token: old.token,
+        royalty_bps: royalty_bps.min(MAX_ROYALTY_BPS),
+    })
+}
@@ -0,0 +1,164 @@
+//! Soroban non-fungible token interface, so wallets and explorers can display
+//! access keys as NFTs. Token IDs are access key IDs.
+
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, String};
+
+use crate::{
+    metadata, AccessKey, Approval, ApprovalEvent, DataKey, DigitalAccessKeysContract, DigitalAccessKeysContractClient,
+    Error, KeyStatus,
+};
+
+/// Collection name reported to wallets
+pub const NAME: &str = "Digital Access Keys";
+
+/// Collection symbol reported to wallets
+pub const SYMBOL: &str = "DAK";
+
+pub trait NonFungibleToken {
+    /// Get the number of keys an address owns, valid or not. See `active_count`
+    /// for the keys that grant access right now.
+    fn balance(env: Env, owner: Address) -> u32;
+
+    /// Get the owner of a key. Burned and revoked keys have none.
+    fn owner_of(env: Env, token_id: u64) -> Result<Address, Error>;
+
+    /// Transfer a key from its owner to another address
+    fn transfer(env: Env, from: Address, to: Address, token_id: u64) -> Result<(), Error>;
+
+    /// Move a key owned by `from` on its behalf. `spender` must be the owner, hold a
+    /// live approval for the key or be an approved operator of the owner.
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, token_id: u64) -> Result<(), Error>;
+
+    /// Allow `approved` to move a key until `live_until_ledger`. A ledger of 0
+    /// removes the approval. Approvals end when the key moves.
+    fn approve(
+        env: Env,
+        approver: Address,
+        approved: Address,
+        token_id: u64,
+        live_until_ledger: u32,
+    ) -> Result<(), Error>;
+
+    /// Get the collection name
+    fn name(env: Env) -> String;
+
+    /// Get the collection symbol
+    fn symbol(env: Env) -> String;
+
+    /// Get the metadata URI of a key, or an empty string if its content has no base URI.
+    /// Burned and revoked keys have no metadata.
+    fn token_uri(env: Env, token_id: u64) -> Result<String, Error>;
+}
+
+#[contractimpl]
+impl NonFungibleToken for DigitalAccessKeysContract {
+    fn balance(env: Env, owner: Address) -> u32 {
+        Self::owned_count(env, owner)
+    }
+
+    fn owner_of(env: Env, token_id: u64) -> Result<Address, Error> {
+        Ok(load_token(&env, token_id)?.owner)
+    }
+
+    fn transfer(env: Env, from: Address, to: Address, token_id: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        from.require_auth();
+        
+        let key = Self::load_key(&env, token_id)?;
+        if key.owner != from {
+            return Err(Error::Unauthorized);
+        }
+        
+        Self::transfer_key(&env, key, &to)
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, token_id: u64) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        spender.require_auth();
+        
+        let key = Self::load_key(&env, token_id)?;
+        if key.owner != from {
+            return Err(Error::Unauthorized);
+        }
+        
+        let approved = Self::get_approved(env.clone(), token_id) == Some(spender.clone());
+        if spender != from && !approved && !Self::is_approved_for_all(env.clone(), from, spender) {
+            return Err(Error::Unauthorized);
+        }
+        
+        Self::transfer_key(&env, key, &to)
+    }
+
+    fn approve(
+        env: Env,
+        approver: Address,
+        approved: Address,
+        token_id: u64,
+        live_until_ledger: u32,
+    ) -> Result<(), Error> {
+        Self::extend_instance(&env);
+        
+        approver.require_auth();
+        
+        // Owners and their operators may approve
+        let key = Self::load_key(&env, token_id)?;
+        if approver != key.owner && !Self::is_approved_for_all(env.clone(), key.owner.clone(), approver) {
+            return Err(Error::Unauthorized);
+        }
+        
+        if live_until_ledger == 0 {
+            env.storage().persistent().remove(&DataKey::Approval(token_id));
+        } else {
+            if live_until_ledger < env.ledger().sequence() {
+                return Err(Error::InvalidExpiration);
+            }
+            
+            let approval = Approval {
+                spender: approved.clone(),
+                expiration_ledger: live_until_ledger,
+            };
+            Self::save(&env, &DataKey::Approval(token_id), &approval);
+        }
+        
+        env.events().publish(
+            (symbol_short!("approve"), &key.owner, token_id),
+            ApprovalEvent {
+                key_id: token_id,
+                owner: key.owner.clone(),
+                spender: approved,
+                expiration_ledger: live_until_ledger,
+            }
+        );
+        
+        Ok(())
+    }
+
+    fn name(env: Env) -> String {
+        String::from_str(&env, NAME)
+    }
+
+    fn symbol(env: Env) -> String {
+        String::from_str(&env, SYMBOL)
+    }
+
+    fn token_uri(env: Env, token_id: u64) -> Result<String, Error> {
+        let key = load_token(&env, token_id)?;
+        
+        match Self::base_uri(env.clone(), key.content_id) {
+            Some(base_uri) => Ok(metadata::token_uri(&env, &base_uri, token_id)),
+            None => Ok(String::from_str(&env, "")),
+        }
+    }
+}
+
+/// Load a key that still exists as a token; burned and revoked keys do not
+fn load_token(env: &Env, token_id: u64) -> Result<AccessKey, Error> {
+    let key = DigitalAccessKeysContract::load_key(env, token_id)?;
+    match key.status {
+        KeyStatus::Burned => Err(Error::KeyBurned),
+        KeyStatus::Revoked => Err(Error::KeyRevoked),
+        _ => Ok(key),
+    }
+}
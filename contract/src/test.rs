@@ -0,0 +1,1677 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env, IntoVal, String, Symbol, Vec,
+};
+
+fn setup(env: &Env) -> (DigitalAccessKeysContractClient<'_>, Address) {
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, DigitalAccessKeysContract);
+    let client = DigitalAccessKeysContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    (client, admin)
+}
+
+fn create_content(env: &Env, client: &DigitalAccessKeysContractClient, price: i128) -> (String, Address) {
+    let content_id = String::from_str(env, "course-1");
+    let creator = Address::generate(env);
+
+    client.register_content(
+        &content_id,
+        &String::from_str(env, "Rust Course"),
+        &String::from_str(env, "Soroban from scratch"),
+        &creator,
+        &price,
+        &100,
+        &DurationPolicy::Bounded(1, 100_000),
+    );
+
+    (content_id, creator)
+}
+
+fn create_token<'a>(env: &Env, client: &DigitalAccessKeysContractClient) -> (TokenClient<'a>, StellarAssetClient<'a>) {
+    let issuer = Address::generate(env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    client.set_payment_token(&sac.address());
+
+    (TokenClient::new(env, &sac.address()), StellarAssetClient::new(env, &sac.address()))
+}
+
+#[test]
+fn test_mint_and_transfer() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+
+    let key_id = client.mint(&user1, &user1, &content_id, &1_000, &true);
+    assert_eq!(client.get_key(&key_id).unwrap().owner, user1);
+
+    client.transfer(&user1, &user2, &key_id);
+    assert_eq!(client.get_key(&key_id).unwrap().owner, user2);
+    assert_eq!(client.balance(&user1), 0);
+    assert_eq!(client.balance(&user2), 1);
+}
+
+#[test]
+fn test_initialize_twice_fails() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let other = Address::generate(&env);
+    assert_eq!(client.try_initialize(&other), Err(Ok(Error::AlreadyInitialized)));
+}
+
+#[test]
+fn test_freeze_requires_compliance_officer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, DigitalAccessKeysContract);
+    let client = DigitalAccessKeysContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    assert_eq!(client.try_freeze_account(&user, &user, &true), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_transfer_errors() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+
+    assert_eq!(client.try_transfer(&user1, &user2, &42), Err(Ok(Error::KeyNotFound)));
+
+    let locked = client.mint(&user1, &user1, &content_id, &1_000, &false);
+    assert_eq!(client.try_transfer(&user1, &user2, &locked), Err(Ok(Error::NotTransferable)));
+
+    let key_id = client.mint(&user1, &user1, &content_id, &1_000, &true);
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert_eq!(client.try_transfer(&user1, &user2, &key_id), Err(Ok(Error::KeyExpired)));
+
+    client.deactivate_expired_key(&key_id);
+    assert_eq!(client.try_transfer(&user1, &user2, &key_id), Err(Ok(Error::KeyInactive)));
+}
+
+#[test]
+fn test_frozen_account_errors() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+
+    let key_id = client.mint(&user1, &user1, &content_id, &1_000, &true);
+
+    client.freeze_account(&admin, &user2, &true);
+    assert!(client.is_frozen(&user2));
+    assert_eq!(client.try_mint(&user2, &user2, &content_id, &1_000, &true), Err(Ok(Error::AccountFrozen)));
+    assert_eq!(client.try_transfer(&user1, &user2, &key_id), Err(Ok(Error::AccountFrozen)));
+
+    client.freeze_account(&admin, &user2, &false);
+    client.transfer(&user1, &user2, &key_id);
+}
+
+#[test]
+fn test_deactivate_missing_key_fails() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    assert_eq!(client.try_deactivate_expired_key(&7), Err(Ok(Error::KeyNotFound)));
+}
+
+#[test]
+fn test_paid_mint_charges_creator_price() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (token, token_admin) = create_token(&env, &client);
+    let (content_id, creator) = create_content(&env, &client, 250);
+
+    let buyer = Address::generate(&env);
+    token_admin.mint(&buyer, &1_000);
+
+    let key_id = client.mint(&buyer, &buyer, &content_id, &1_000, &true);
+    assert_eq!(client.get_key(&key_id).unwrap().owner, buyer);
+    assert_eq!(token.balance(&buyer), 750);
+    assert_eq!(token.balance(&creator), 250);
+}
+
+#[test]
+fn test_paid_mint_errors() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let buyer = Address::generate(&env);
+    let unknown = String::from_str(&env, "unknown");
+    assert_eq!(client.try_mint(&buyer, &buyer, &unknown, &1_000, &true), Err(Ok(Error::ContentNotFound)));
+
+    let (content_id, _) = create_content(&env, &client, 250);
+    assert_eq!(client.try_mint(&buyer, &buyer, &content_id, &1_000, &true), Err(Ok(Error::PaymentTokenNotSet)));
+
+    // Buyer without funds cannot mint once a token is configured
+    create_token(&env, &client);
+    assert!(client.try_mint(&buyer, &buyer, &content_id, &1_000, &true).is_err());
+    assert_eq!(client.balance(&buyer), 0);
+}
+
+#[test]
+fn test_negative_price_rejected() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let creator = Address::generate(&env);
+    let result = client.try_register_content(
+        &String::from_str(&env, "course-1"),
+        &String::from_str(&env, "Rust Course"),
+        &String::from_str(&env, "Soroban from scratch"),
+        &creator,
+        &-1,
+        &100,
+        &DurationPolicy::Bounded(1, 100_000),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidPrice)));
+}
+
+#[test]
+fn test_supply_cap_enforced() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let content_id = String::from_str(&env, "limited");
+    let creator = Address::generate(&env);
+    client.register_content(
+        &content_id,
+        &String::from_str(&env, "Limited Edition"),
+        &String::from_str(&env, "Only two keys"),
+        &creator,
+        &0,
+        &2,
+        &DurationPolicy::Bounded(1, 100_000),
+    );
+    assert_eq!(client.remaining_supply(&content_id), Some(2));
+
+    let user = Address::generate(&env);
+    client.mint(&user, &user, &content_id, &1_000, &true);
+    assert_eq!(client.remaining_supply(&content_id), Some(1));
+
+    client.mint(&user, &user, &content_id, &1_000, &true);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (Symbol::new(&env, "supply_exhausted"), content_id.clone()).into_val(&env));
+    assert_eq!(client.remaining_supply(&content_id), Some(0));
+    assert_eq!(client.try_mint(&user, &user, &content_id, &1_000, &true), Err(Ok(Error::SupplyExhausted)));
+    assert_eq!(client.balance(&user), 2);
+}
+
+#[test]
+fn test_unlimited_supply() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let content_id = String::from_str(&env, "open");
+    let creator = Address::generate(&env);
+    client.register_content(
+        &content_id,
+        &String::from_str(&env, "Open Edition"),
+        &String::from_str(&env, "No cap"),
+        &creator,
+        &0,
+        &UNLIMITED_SUPPLY,
+        &DurationPolicy::Bounded(1, 100_000),
+    );
+
+    let user = Address::generate(&env);
+    for _ in 0..5 {
+        client.mint(&user, &user, &content_id, &1_000, &true);
+    }
+    assert_eq!(client.remaining_supply(&content_id), None);
+    assert_eq!(
+        client.try_remaining_supply(&String::from_str(&env, "unknown")),
+        Err(Ok(Error::ContentNotFound))
+    );
+}
+
+#[test]
+fn test_creator_gifts_keys() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    create_token(&env, &client);
+    let (content_id, creator) = create_content(&env, &client, 250);
+
+    // Gift keys are free, even without funds
+    let fan = Address::generate(&env);
+    let key_id = client.mint(&creator, &fan, &content_id, &1_000, &true);
+    assert_eq!(client.get_key(&key_id).unwrap().owner, fan);
+    assert_eq!(client.balance(&creator), 0);
+}
+
+#[test]
+fn test_mint_policies() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+
+    client.set_mint_policy(&content_id, &MintPolicy::CreatorOnly);
+    assert_eq!(client.try_mint(&user, &user, &content_id, &1_000, &true), Err(Ok(Error::Unauthorized)));
+    client.mint(&creator, &user, &content_id, &1_000, &true);
+
+    client.set_mint_policy(&content_id, &MintPolicy::Allowlist);
+    assert_eq!(client.try_mint(&user, &user, &content_id, &1_000, &true), Err(Ok(Error::NotAllowlisted)));
+    client.set_allowlisted(&content_id, &user, &true);
+    assert!(client.is_allowlisted(&content_id, &user));
+    client.mint(&user, &user, &content_id, &1_000, &true);
+
+    client.set_mint_policy(&content_id, &MintPolicy::AdminOnly);
+    assert_eq!(client.try_mint(&user, &user, &content_id, &1_000, &true), Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.try_mint(&creator, &user, &content_id, &1_000, &true), Err(Ok(Error::Unauthorized)));
+    client.mint(&admin, &user, &content_id, &1_000, &true);
+
+    assert_eq!(client.balance(&user), 3);
+}
+
+#[test]
+fn test_two_step_admin_handover() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    assert_eq!(client.admin(), admin);
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+
+    client.propose_admin(&new_admin);
+    assert_eq!(client.pending_admin(), Some(new_admin.clone()));
+    assert_eq!(client.admin(), admin);
+
+    client.accept_admin();
+    assert_eq!(client.admin(), new_admin);
+    assert_eq!(client.pending_admin(), None);
+}
+
+#[test]
+fn test_cancel_admin_transfer() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    assert_eq!(client.try_cancel_admin_transfer(), Err(Ok(Error::NoPendingAdmin)));
+
+    client.propose_admin(&new_admin);
+    client.cancel_admin_transfer();
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("adm_cncl"), new_admin.clone()).into_val(&env));
+    let event: AdminTransferCancelledEvent = data.into_val(&env);
+    assert_eq!(event, AdminTransferCancelledEvent { admin: admin.clone(), cancelled: new_admin.clone() });
+    assert_eq!(client.pending_admin(), None);
+    assert_eq!(client.try_accept_admin(), Err(Ok(Error::NoPendingAdmin)));
+    assert_eq!(client.admin(), admin);
+}
+
+#[test]
+fn test_initialize_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, DigitalAccessKeysContract);
+    let client = DigitalAccessKeysContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    // Nobody can appoint an admin without their signature
+    assert!(client.try_initialize(&admin).is_err());
+    assert_eq!(client.try_admin(), Err(Ok(Error::AdminNotSet)));
+
+    env.mock_all_auths();
+    client.initialize(&admin);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("init"), admin.clone()).into_val(&env));
+    assert_eq!(client.admin(), admin);
+}
+
+#[test]
+fn test_reinitialize_keeps_admin_and_keys() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    let attacker = Address::generate(&env);
+
+    let first = client.mint(&user, &user, &content_id, &1_000, &true);
+    assert_eq!(client.try_initialize(&attacker), Err(Ok(Error::AlreadyInitialized)));
+    assert_eq!(client.admin(), admin);
+
+    let second = client.mint(&user, &user, &content_id, &1_000, &true);
+    assert_ne!(first, second);
+    assert_eq!(client.get_key(&first).unwrap().owner, user);
+}
+
+#[test]
+fn test_content_registry_ownership() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let squatter = Address::generate(&env);
+
+    let result = client.try_register_content(
+        &content_id,
+        &String::from_str(&env, "Stolen"),
+        &String::from_str(&env, "Not mine"),
+        &squatter,
+        &0,
+        &100,
+        &DurationPolicy::Bounded(1, 100_000),
+    );
+    assert_eq!(result, Err(Ok(Error::ContentAlreadyExists)));
+    assert_eq!(client.get_content_metadata(&content_id).unwrap().creator, creator);
+
+    client.update_content(
+        &content_id,
+        &String::from_str(&env, "Rust Course v2"),
+        &String::from_str(&env, "Updated"),
+        &500,
+        &10,
+    );
+    let metadata = client.get_content_metadata(&content_id).unwrap();
+    assert_eq!(metadata.price, 500);
+    assert_eq!(metadata.creator, creator);
+
+    let new_creator = Address::generate(&env);
+    client.transfer_content_ownership(&content_id, &new_creator);
+    assert_eq!(client.get_content_metadata(&content_id).unwrap().creator, new_creator);
+}
+
+#[test]
+fn test_update_content_requires_creator_auth() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+
+    env.set_auths(&[]);
+    let result = client.try_update_content(
+        &content_id,
+        &String::from_str(&env, "Hijacked"),
+        &String::from_str(&env, "Hijacked"),
+        &0,
+        &100,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_retired_content_keeps_existing_keys() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+
+    let key_id = client.mint(&user, &user, &content_id, &1_000, &true);
+    client.retire_content(&content_id);
+
+    assert!(client.get_content_metadata(&content_id).unwrap().retired);
+    assert!(client.is_key_valid(&key_id));
+    assert_eq!(client.try_mint(&creator, &user, &content_id, &1_000, &true), Err(Ok(Error::ContentRetired)));
+    assert_eq!(client.try_retire_content(&content_id), Err(Ok(Error::ContentRetired)));
+
+    let result = client.try_register_content(
+        &content_id,
+        &String::from_str(&env, "Reused"),
+        &String::from_str(&env, "Reused"),
+        &user,
+        &0,
+        &100,
+        &DurationPolicy::Bounded(1, 100_000),
+    );
+    assert_eq!(result, Err(Ok(Error::ContentAlreadyExists)));
+}
+
+#[test]
+fn test_marketplace_buy_listing() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (token, token_admin) = create_token(&env, &client);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    token_admin.mint(&buyer, &1_000);
+
+    let key_id = client.mint(&seller, &seller, &content_id, &1_000, &true);
+    client.list_key(&key_id, &400, &token.address);
+    assert_eq!(client.get_listing(&key_id).unwrap().price, 400);
+
+    client.buy_listing(&buyer, &key_id);
+    assert_eq!(client.get_key(&key_id).unwrap().owner, buyer);
+    assert_eq!(token.balance(&seller), 400);
+    assert_eq!(token.balance(&buyer), 600);
+    assert_eq!(client.get_listing(&key_id), None);
+    assert_eq!(client.try_buy_listing(&buyer, &key_id), Err(Ok(Error::ListingNotFound)));
+}
+
+#[test]
+fn test_marketplace_listing_invalidation() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (token, _) = create_token(&env, &client);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let seller = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let locked = client.mint(&seller, &seller, &content_id, &1_000, &false);
+    assert_eq!(client.try_list_key(&locked, &400, &token.address), Err(Ok(Error::NotTransferable)));
+
+    let key_id = client.mint(&seller, &seller, &content_id, &1_000, &true);
+    assert_eq!(client.try_list_key(&key_id, &0, &token.address), Err(Ok(Error::InvalidPrice)));
+
+    // Frozen seller
+    client.list_key(&key_id, &400, &token.address);
+    client.freeze_account(&admin, &seller, &true);
+    assert_eq!(client.get_listing(&key_id), None);
+    client.freeze_account(&admin, &seller, &false);
+    assert!(client.get_listing(&key_id).is_some());
+
+    // Owner change
+    client.transfer(&seller, &other, &key_id);
+    assert_eq!(client.get_listing(&key_id), None);
+
+    // Expiry
+    client.list_key(&key_id, &400, &token.address);
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert_eq!(client.get_listing(&key_id), None);
+    assert_eq!(client.try_buy_listing(&seller, &key_id), Err(Ok(Error::ListingNotFound)));
+
+    client.cancel_listing(&key_id);
+    assert_eq!(client.try_cancel_listing(&key_id), Err(Ok(Error::ListingNotFound)));
+}
+
+#[test]
+fn test_royalties_on_secondary_sale() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (token, token_admin) = create_token(&env, &client);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    token_admin.mint(&buyer, &1_000);
+
+    assert_eq!(client.try_set_royalty(&content_id, &(MAX_ROYALTY_BPS + 1)), Err(Ok(Error::InvalidRoyalty)));
+    client.set_royalty(&content_id, &1_000);
+    assert_eq!(client.royalty_info(&content_id, &400), (creator.clone(), 40));
+
+    let key_id = client.mint(&seller, &seller, &content_id, &1_000, &true);
+    client.list_key(&key_id, &400, &token.address);
+    client.buy_listing(&buyer, &key_id);
+
+    assert_eq!(token.balance(&creator), 40);
+    assert_eq!(token.balance(&seller), 360);
+    assert_eq!(token.balance(&buyer), 600);
+}
+
+#[test]
+fn test_royalty_fixed_at_listing() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (token, token_admin) = create_token(&env, &client);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    token_admin.mint(&buyer, &1_000);
+
+    client.set_royalty(&content_id, &500);
+    let key_id = client.mint(&seller, &seller, &content_id, &1_000, &true);
+    client.list_key(&key_id, &1_000, &token.address);
+    assert_eq!(client.get_listing(&key_id).unwrap().royalty_bps, 500);
+
+    // Raising the royalty afterwards does not touch the seller's cut
+    client.set_royalty(&content_id, &MAX_ROYALTY_BPS);
+    client.buy_listing(&buyer, &key_id);
+    assert_eq!(token.balance(&creator), 50);
+    assert_eq!(token.balance(&seller), 950);
+
+    // Huge prices report an error instead of overflowing
+    assert_eq!(client.try_royalty_info(&content_id, &i128::MAX), Err(Ok(Error::ArithmeticOverflow)));
+    let other = client.mint(&seller, &seller, &content_id, &1_000, &true);
+    assert_eq!(client.try_list_key(&other, &i128::MAX, &token.address), Err(Ok(Error::ArithmeticOverflow)));
+}
+
+#[test]
+fn test_renew_extends_and_reactivates() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (token, token_admin) = create_token(&env, &client);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    token_admin.mint(&user, &1_000);
+
+    let key_id = client.mint(&user, &user, &content_id, &1_000, &true);
+    assert_eq!(client.try_renew(&key_id, &1), Err(Ok(Error::RenewalNotAvailable)));
+
+    client.set_renewal_terms(&content_id, &500, &100);
+    assert_eq!(client.try_renew(&key_id, &0), Err(Ok(Error::InvalidPeriods)));
+
+    // Renewing an active key extends from its current expiry
+    assert_eq!(client.renew(&key_id, &2), 2_000);
+    assert_eq!(token.balance(&creator), 200);
+
+    // Renewals only report a renewal, not a purchase
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("renewed"), user.clone()).into_val(&env));
+    let purchase: Val = symbol_short!("purchase").into_val(&env);
+    assert!(!env.events().all().iter().any(|(contract, topics, _)| {
+        contract == client.address && topics.get(0).unwrap().shallow_eq(&purchase)
+    }));
+
+    // Renewing a deactivated key extends from now and reactivates it
+    env.ledger().with_mut(|li| li.timestamp = 5_000);
+    client.deactivate_expired_key(&key_id);
+    assert_eq!(client.active_count(&user), 0);
+
+    assert_eq!(client.renew(&key_id, &1), 5_500);
+    let key = client.get_key(&key_id).unwrap();
+    assert_eq!(key.status, KeyStatus::Active);
+    assert!(client.is_key_valid(&key_id));
+    assert_eq!(client.balance(&user), 1);
+    assert_eq!(token.balance(&user), 700);
+
+    // Prices that overflow are rejected
+    client.set_renewal_terms(&content_id, &500, &i128::MAX);
+    assert_eq!(client.try_renew(&key_id, &2), Err(Ok(Error::ArithmeticOverflow)));
+}
+
+#[test]
+fn test_duration_policies() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+
+    // Bounded: expiry is computed from the ledger time
+    let key_id = client.mint(&user, &user, &content_id, &500, &true);
+    assert_eq!(client.get_key(&key_id).unwrap().expires_at, 10_500);
+    assert_eq!(client.try_mint(&user, &user, &content_id, &0, &true), Err(Ok(Error::InvalidDuration)));
+    assert_eq!(client.try_mint(&user, &user, &content_id, &u64::MAX, &true), Err(Ok(Error::InvalidDuration)));
+
+    client.set_duration_policy(&content_id, &DurationPolicy::Fixed(3_600));
+    assert_eq!(client.try_mint(&user, &user, &content_id, &500, &true), Err(Ok(Error::InvalidDuration)));
+    let key_id = client.mint(&user, &user, &content_id, &3_600, &true);
+    assert_eq!(client.get_key(&key_id).unwrap().expires_at, 13_600);
+
+    client.set_duration_policy(&content_id, &DurationPolicy::Perpetual);
+    assert_eq!(client.try_mint(&user, &user, &content_id, &500, &true), Err(Ok(Error::InvalidDuration)));
+    let key_id = client.mint(&user, &user, &content_id, &0, &true);
+    assert_eq!(client.get_key(&key_id).unwrap().expires_at, NEVER_EXPIRES);
+
+    assert_eq!(
+        client.try_set_duration_policy(&content_id, &DurationPolicy::Bounded(100, 10)),
+        Err(Ok(Error::InvalidDuration))
+    );
+    assert_eq!(
+        client.try_set_duration_policy(&content_id, &DurationPolicy::Fixed(0)),
+        Err(Ok(Error::InvalidDuration))
+    );
+}
+
+fn assert_counters_consistent(client: &DigitalAccessKeysContractClient, owners: &[&Address]) {
+    for owner in owners {
+        let keys = client.keys_of(owner, &0, &MAX_PAGE_SIZE);
+        assert_eq!(client.owned_count(owner), keys.len());
+
+        let mut valid = 0;
+        for key_id in keys.iter() {
+            assert_eq!(client.get_key(&key_id).unwrap().owner, **owner);
+            if client.is_key_valid(&key_id) {
+                valid += 1;
+            }
+        }
+        assert_eq!(client.active_count(owner), valid);
+        assert_eq!(client.balance(owner), keys.len());
+    }
+}
+
+#[test]
+fn test_active_count_counts_only_valid_keys() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+
+    client.mint(&user, &user, &content_id, &1_000, &true);
+    client.mint(&user, &user, &content_id, &5_000, &true);
+    assert_eq!(client.active_count(&user), 2);
+    assert_eq!(client.balance_of(&user, &content_id), 2);
+
+    // Natural expiry is reflected without anyone calling deactivate_expired_key
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    assert_eq!(client.active_count(&user), 1);
+    assert_eq!(client.balance_of(&user, &content_id), 1);
+    assert_eq!(client.owned_count(&user), 2);
+    // The NFT balance counts tokens held, expired or not
+    assert_eq!(client.balance(&user), 2);
+    assert_eq!(client.balance_of(&user, &String::from_str(&env, "other")), 0);
+}
+
+#[test]
+fn test_counters_never_diverge() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    client.set_renewal_terms(&content_id, &1_000, &0);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let owners = [&alice, &bob];
+
+    let k1 = client.mint(&alice, &alice, &content_id, &1_000, &true);
+    let k2 = client.mint(&alice, &alice, &content_id, &3_000, &true);
+    let k3 = client.mint(&bob, &bob, &content_id, &1_000, &true);
+    assert_counters_consistent(&client, &owners);
+
+    client.transfer(&alice, &bob, &k1);
+    client.transfer(&bob, &alice, &k3);
+    assert_counters_consistent(&client, &owners);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    assert_counters_consistent(&client, &owners);
+
+    client.deactivate_expired_key(&k1);
+    client.deactivate_expired_key(&k1);
+    client.deactivate_expired_key(&k2);
+    assert_counters_consistent(&client, &owners);
+
+    client.renew(&k1, &1);
+    client.transfer(&alice, &bob, &k2);
+    client.transfer(&bob, &alice, &k1);
+    assert_counters_consistent(&client, &owners);
+
+    assert_eq!(client.owned_count(&alice), 2);
+    assert_eq!(client.owned_count(&bob), 1);
+    assert_eq!(client.active_count(&alice), 1);
+}
+
+#[test]
+fn test_has_access() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let other_content = String::from_str(&env, "course-2");
+    let user = Address::generate(&env);
+    let friend = Address::generate(&env);
+
+    assert_eq!(client.has_access(&user, &content_id), None);
+
+    let short = client.mint(&user, &user, &content_id, &1_000, &true);
+    let long = client.mint(&user, &user, &content_id, &5_000, &true);
+    assert!(client.has_access(&user, &content_id).is_some());
+    assert_eq!(client.has_access(&user, &other_content), None);
+
+    // Expired keys do not grant access, other valid keys still do
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    assert_eq!(client.has_access(&user, &content_id).unwrap().id, long);
+
+    // Frozen accounts have no access
+    client.freeze_account(&admin, &user, &true);
+    assert_eq!(client.has_access(&user, &content_id), None);
+    client.freeze_account(&admin, &user, &false);
+
+    // Access follows the key on transfer
+    client.transfer(&user, &friend, &long);
+    assert_eq!(client.has_access(&user, &content_id), None);
+    assert_eq!(client.has_access(&friend, &content_id).unwrap().id, long);
+    assert_eq!(client.get_key(&short).unwrap().owner, user);
+}
+
+#[test]
+fn test_paginated_enumeration() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+
+    let mut minted = Vec::new(&env);
+    for _ in 0..5 {
+        minted.push_back(client.mint(&alice, &alice, &content_id, &1_000, &true));
+    }
+    assert_eq!(client.total_keys(), 5);
+    assert_eq!(client.total_contents(), 1);
+
+    let first = client.keys_of(&alice, &0, &2);
+    let second = client.keys_of(&alice, &2, &2);
+    let third = client.keys_of(&alice, &4, &2);
+    assert_eq!(first.len() + second.len() + third.len(), 5);
+    assert_eq!(client.keys_of(&alice, &5, &2).len(), 0);
+    assert_eq!(client.get_user_keys(&alice), client.keys_of(&alice, &0, &MAX_PAGE_SIZE));
+    assert_eq!(client.keys_for_content(&content_id, &0, &10), minted);
+
+    // Removing a key from the middle keeps the remaining keys enumerable
+    let moved = minted.get(1).unwrap();
+    client.transfer(&alice, &bob, &moved);
+    let remaining = client.keys_of(&alice, &0, &10);
+    assert_eq!(remaining.len(), 4);
+    assert!(!remaining.contains(moved));
+    for key_id in minted.iter().filter(|id| *id != moved) {
+        assert!(remaining.contains(key_id));
+    }
+    assert_eq!(client.keys_of(&bob, &0, &10), Vec::from_array(&env, [moved]));
+
+    // Content enumeration is unaffected by ownership changes
+    assert_eq!(client.keys_for_content(&content_id, &0, &10), minted);
+    assert_eq!(client.keys_for_content(&content_id, &3, &10).len(), 2);
+}
+
+fn key_ttl(env: &Env, client: &DigitalAccessKeysContractClient, key_id: u64) -> u32 {
+    env.as_contract(&client.address, || {
+        env.storage().persistent().get_ttl(&DataKey::AccessKey(key_id))
+    })
+}
+
+#[test]
+fn test_key_ttl_follows_expiry() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    let config = client.ttl_config();
+    let duration = 60 * 24 * 60 * 60;
+    client.set_duration_policy(&content_id, &DurationPolicy::Bounded(1, duration));
+
+    // Short keys live as long as the index entries written with them
+    let short = client.mint(&user, &user, &content_id, &500, &true);
+    assert_eq!(key_ttl(&env, &client, short), config.persistent_extend_to);
+
+    // Longer keys follow their expiry and keep the keys listed with them alive
+    let long = client.mint(&user, &user, &content_id, &duration, &true);
+    let lifetime = (duration / 5) as u32 + config.expired_key_grace;
+    assert_eq!(key_ttl(&env, &client, long), lifetime);
+    assert_eq!(key_ttl(&env, &client, short), lifetime);
+
+    // Anyone can top the TTL back up while the key is valid
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 10_000;
+        li.timestamp = 50_000;
+    });
+    client.bump_key(&long);
+    assert_eq!(key_ttl(&env, &client, long), lifetime - 10_000);
+}
+
+#[test]
+fn test_expired_key_outlives_its_grace_while_listed() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    let duration = 60 * 24 * 60 * 60;
+    client.set_duration_policy(&content_id, &DurationPolicy::Bounded(1, duration));
+
+    let short = client.mint(&user, &user, &content_id, &10, &true);
+    let long = client.mint(&user, &user, &content_id, &duration, &true);
+
+    // Past the short key's expiry plus grace, then past the default storage TTL
+    for _ in 0..2 {
+        env.ledger().with_mut(|li| {
+            li.sequence_number += 20 * DAY_IN_LEDGERS;
+            li.timestamp += 20 * 24 * 60 * 60;
+        });
+        client.bump_key(&long);
+    }
+    assert_eq!(client.has_access(&user, &content_id).map(|key| key.id), Some(long));
+    assert_eq!(client.balance(&user), 2);
+    assert_eq!(client.active_count(&user), 1);
+    assert_eq!(client.balance_of(&user, &content_id), 1);
+    assert_eq!(client.keys_of(&user, &0, &MAX_PAGE_SIZE).len(), 2);
+
+    // Expired keys are kept alive as long as they are listed for their owner
+    client.bump_key(&short);
+    assert_eq!(key_ttl(&env, &client, short), key_ttl(&env, &client, long));
+}
+
+#[test]
+fn test_key_ttl_covers_access_checks() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    let renter = Address::generate(&env);
+    let grace = client.ttl_config().expired_key_grace;
+
+    // Outlive the default 30 day storage TTL
+    let duration = 40 * 24 * 60 * 60;
+    client.set_duration_policy(&content_id, &DurationPolicy::Bounded(1, duration));
+    client.suspend_content(&creator, &content_id, &true);
+    client.suspend_content(&creator, &content_id, &false);
+    client.freeze_account(&admin, &user, &true);
+    client.freeze_account(&admin, &user, &false);
+    let key_id = client.mint(&user, &user, &content_id, &duration, &true);
+    client.set_user(&key_id, &Some(renter.clone()), &1_000);
+
+    let lifetime = (duration / 5) as u32 + grace;
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        for entry in [
+            DataKey::AccessKey(key_id),
+            DataKey::OwnerKeyIndex(key_id),
+            DataKey::OwnerKeyAt(user.clone(), 0),
+            DataKey::OwnedCount(user.clone()),
+            DataKey::OwnerContentKeys(user.clone(), content_id.clone()),
+            DataKey::ContentMeta(content_id.clone()),
+            DataKey::RenterContentKeys(renter.clone(), content_id.clone()),
+        ] {
+            assert!(storage.get_ttl(&entry) >= lifetime);
+        }
+    });
+}
+
+#[test]
+fn test_ttl_config() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let mut config = client.ttl_config();
+    config.persistent_threshold = config.persistent_extend_to + 1;
+    assert_eq!(client.try_set_ttl_config(&config), Err(Ok(Error::InvalidTtlConfig)));
+
+    config.persistent_threshold = 10_000;
+    config.persistent_extend_to = 20_000;
+    client.set_ttl_config(&config);
+    assert_eq!(client.ttl_config(), config);
+
+    let (content_id, _) = create_content(&env, &client, 0);
+    let ttl = env.as_contract(&client.address, || {
+        env.storage().persistent().get_ttl(&DataKey::ContentMeta(content_id.clone()))
+    });
+    assert_eq!(ttl, 20_000);
+}
+
+#[test]
+fn test_migrate_v2_storage() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let (token, _) = create_token(&env, &client);
+    let unused = String::from_str(&env, "unused");
+    client.register_content(
+        &unused,
+        &String::from_str(&env, "Unused"),
+        &String::from_str(&env, "No keys yet"),
+        &Address::generate(&env),
+        &0,
+        &10,
+        &DurationPolicy::Perpetual,
+    );
+    let user = Address::generate(&env);
+    let listed = client.mint(&user, &user, &content_id, &1_000, &true);
+    let expired = client.mint(&user, &user, &content_id, &1_000, &true);
+    client.list_key(&listed, &500, &token.address);
+
+    // Rewrite the data in the schema version 2 layout, which allowed royalties up to 100%
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        env.storage().instance().set(&symbol_short!("SCHEMA"), &2u32);
+        for id in [content_id.clone(), unused.clone()] {
+            let mut metadata: ContentMetadata = storage.get(&DataKey::ContentMeta(id.clone())).unwrap();
+            metadata.royalty_bps = 5_000;
+            storage.set(&DataKey::ContentMeta(id), &metadata);
+        }
+        for (key_id, is_active) in [(listed, true), (expired, false)] {
+            storage.set(
+                &DataKey::AccessKey(key_id),
+                &migration::AccessKeyV2 {
+                    id: key_id,
+                    owner: user.clone(),
+                    content_id: content_id.clone(),
+                    expires_at: 1_000,
+                    is_active,
+                    transferable: true,
+                },
+            );
+        }
+        storage.set(
+            &DataKey::Listing(listed),
+            &migration::ListingV2 {
+                key_id: listed,
+                seller: user.clone(),
+                price: 500,
+                token: token.address.clone(),
+            },
+        );
+    });
+    assert_eq!(client.schema_version(), 2);
+    assert_eq!(client.pending_migration(), 2);
+
+    // A partial batch keeps the old schema version
+    client.migrate(&Vec::new(&env), &1);
+    assert_eq!(client.schema_version(), 2);
+    assert_eq!(client.pending_migration(), 1);
+    assert_eq!(client.key_status(&listed), KeyStatus::Active);
+
+    client.migrate(&Vec::from_array(&env, [unused.clone()]), &1);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+    assert_eq!(client.pending_migration(), 0);
+    assert_eq!(client.get_key(&expired).unwrap().status, KeyStatus::Expired);
+    assert_eq!(client.get_listing(&listed).unwrap().royalty_bps, MAX_ROYALTY_BPS);
+    assert_eq!(client.get_content_metadata(&content_id).unwrap().royalty_bps, MAX_ROYALTY_BPS);
+    assert_eq!(client.get_content_metadata(&unused).unwrap().royalty_bps, MAX_ROYALTY_BPS);
+
+    // Migrating again changes nothing
+    client.migrate(&Vec::from_array(&env, [content_id.clone()]), &10);
+    assert_eq!(client.owned_count(&user), 2);
+    assert_eq!(client.total_keys(), 2);
+    assert_eq!(client.total_contents(), 2);
+
+    // Migrated keys behave like native ones
+    let friend = Address::generate(&env);
+    assert_eq!(client.try_transfer(&user, &friend, &expired), Err(Ok(Error::KeyInactive)));
+    assert_eq!(client.has_access(&user, &content_id).unwrap().id, listed);
+}
+
+#[test]
+fn test_upgrade_requires_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, DigitalAccessKeysContract);
+    let client = DigitalAccessKeysContractClient::new(&env, &contract_id);
+
+    let hash = BytesN::from_array(&env, &[0; 32]);
+    assert_eq!(client.try_upgrade(&hash), Err(Ok(Error::AdminNotSet)));
+}
+
+/// Release build of schema version 2, the first version with `upgrade`
+const V2_WASM: &[u8] = include_bytes!("../testdata/digital_access_keys_v2.wasm");
+
+/// Release build of the current sources, see `test_upgrade_from_v2`
+fn current_wasm() -> std::vec::Vec<u8> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/target/wasm32-unknown-unknown/release/digital_access_keys.wasm");
+    std::fs::read(path).expect("build the contract with `cargo rustc --target wasm32-unknown-unknown --release --crate-type cdylib`")
+}
+
+#[test]
+#[ignore = "needs the release wasm of the current sources"]
+fn test_upgrade_from_v2() {
+    let env = Env::default();
+    env.mock_all_auths();
+    // Instantiating wasm is metered, unlike native test contracts
+    env.budget().reset_unlimited();
+
+    let contract_id = env.register_contract_wasm(None, V2_WASM);
+    let client = DigitalAccessKeysContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let (token, _) = create_token(&env, &client);
+    let user = Address::generate(&env);
+    let listed = client.mint(&user, &user, &content_id, &1_000, &true);
+    let kept = client.mint(&user, &user, &content_id, &1_000, &true);
+    client.list_key(&listed, &500, &token.address);
+    assert_eq!(client.schema_version(), 2);
+
+    let hash = env.deployer().upload_contract_wasm(current_wasm().as_slice());
+
+    // Only the admin can swap the code
+    let attacker = Address::generate(&env);
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "upgrade",
+                args: (hash.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_upgrade(&hash);
+    assert!(result.is_err());
+    // Still running version 2, which has no `key_status`
+    assert!(client.try_key_status(&kept).is_err());
+
+    client.upgrade(&hash);
+    assert_eq!(client.schema_version(), 2);
+    assert_eq!(client.pending_migration(), 2);
+
+    client.migrate(&Vec::new(&env), &10);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+    assert_eq!(client.key_status(&kept), KeyStatus::Active);
+    assert_eq!(client.keys_of(&user, &0, &10), Vec::from_array(&env, [listed, kept]));
+    assert_eq!(client.get_listing(&listed).unwrap().royalty_bps, 0);
+    assert_eq!(client.total_keys(), 2);
+
+    let friend = Address::generate(&env);
+    client.transfer(&user, &friend, &kept);
+    assert_eq!(client.has_access(&friend, &content_id).unwrap().id, kept);
+}
+
+#[test]
+fn test_global_pause() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (token, _) = create_token(&env, &client);
+    let (content_id, _) = create_content(&env, &client, 0);
+    client.set_renewal_terms(&content_id, &1_000, &0);
+    let user = Address::generate(&env);
+    let friend = Address::generate(&env);
+
+    let key_id = client.mint(&user, &user, &content_id, &1_000, &true);
+    client.list_key(&key_id, &100, &token.address);
+
+    client.pause();
+    assert!(client.is_paused());
+    assert_eq!(client.try_mint(&user, &user, &content_id, &1_000, &true), Err(Ok(Error::ContractPaused)));
+    assert_eq!(client.try_transfer(&user, &friend, &key_id), Err(Ok(Error::ContractPaused)));
+    assert_eq!(client.try_list_key(&key_id, &100, &token.address), Err(Ok(Error::ContractPaused)));
+    assert_eq!(client.try_buy_listing(&friend, &key_id), Err(Ok(Error::ContractPaused)));
+    assert_eq!(client.try_renew(&key_id, &1), Err(Ok(Error::ContractPaused)));
+
+    // Reads keep working
+    assert!(client.is_key_valid(&key_id));
+    assert_eq!(client.has_access(&user, &content_id).unwrap().id, key_id);
+    assert_eq!(client.get_listing(&key_id).unwrap().price, 100);
+
+    client.unpause();
+    client.transfer(&user, &friend, &key_id);
+}
+
+#[test]
+fn test_content_suspension() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    let friend = Address::generate(&env);
+
+    let key_id = client.mint(&user, &user, &content_id, &1_000, &true);
+
+    client.suspend_content(&creator, &content_id, &true);
+    assert!(client.is_content_suspended(&content_id));
+    assert_eq!(client.has_access(&user, &content_id), None);
+    assert_eq!(client.try_mint(&creator, &user, &content_id, &1_000, &true), Err(Ok(Error::ContentSuspended)));
+    assert_eq!(client.try_transfer(&user, &friend, &key_id), Err(Ok(Error::ContentSuspended)));
+    assert!(!client.is_paused());
+
+    assert_eq!(client.suspended_by(&content_id), Some(creator.clone()));
+
+    client.suspend_content(&creator, &content_id, &false);
+    assert_eq!(client.has_access(&user, &content_id).unwrap().id, key_id);
+    client.transfer(&user, &friend, &key_id);
+}
+
+#[test]
+fn test_moderator_suspension_outranks_creator() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let moderator = Address::generate(&env);
+    client.grant_role(&admin, &Role::ContentModerator, &moderator);
+
+    // A moderator takes over the creator's own suspension
+    client.suspend_content(&creator, &content_id, &true);
+    client.suspend_content(&moderator, &content_id, &true);
+    assert_eq!(client.suspended_by(&content_id), Some(moderator.clone()));
+
+    client.suspend_content(&creator, &content_id, &true);
+    assert_eq!(client.suspended_by(&content_id), Some(moderator.clone()));
+    assert_eq!(client.try_suspend_content(&creator, &content_id, &false), Err(Ok(Error::Unauthorized)));
+    assert!(client.is_content_suspended(&content_id));
+
+    // Any moderator, including the admin, can lift it
+    client.suspend_content(&admin, &content_id, &false);
+    assert!(!client.is_content_suspended(&content_id));
+    assert_eq!(client.suspended_by(&content_id), None);
+}
+
+#[test]
+fn test_roles() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let officer = Address::generate(&env);
+    let moderator = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    // The admin implicitly holds every role
+    assert!(client.has_role(&Role::SuperAdmin, &admin));
+    assert!(!client.has_role(&Role::ComplianceOfficer, &officer));
+
+    // Only role holders may freeze accounts or moderate content
+    assert_eq!(client.try_freeze_account(&officer, &user, &true), Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.try_grant_role(&officer, &Role::ComplianceOfficer, &officer), Err(Ok(Error::Unauthorized)));
+    client.grant_role(&admin, &Role::ComplianceOfficer, &officer);
+    client.freeze_account(&officer, &user, &true);
+    assert!(client.is_frozen(&user));
+    client.freeze_account(&officer, &user, &false);
+
+    assert_eq!(client.try_suspend_content(&moderator, &content_id, &true), Err(Ok(Error::Unauthorized)));
+    client.grant_role(&admin, &Role::ContentModerator, &moderator);
+    client.suspend_content(&moderator, &content_id, &true);
+    assert!(client.is_content_suspended(&content_id));
+    client.suspend_content(&moderator, &content_id, &false);
+
+    // Creators appoint their own minter operators
+    client.set_mint_policy(&content_id, &MintPolicy::CreatorOnly);
+    assert_eq!(client.try_mint(&operator, &user, &content_id, &1_000, &true), Err(Ok(Error::Unauthorized)));
+    assert_eq!(
+        client.try_grant_role(&operator, &Role::MinterOperator(creator.clone()), &operator),
+        Err(Ok(Error::Unauthorized))
+    );
+    // The admin can neither mint for the creator nor appoint someone who can
+    assert!(!client.has_role(&Role::MinterOperator(creator.clone()), &admin));
+    assert_eq!(client.try_mint(&admin, &user, &content_id, &1_000, &true), Err(Ok(Error::Unauthorized)));
+    assert_eq!(
+        client.try_grant_role(&admin, &Role::MinterOperator(creator.clone()), &operator),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.grant_role(&creator, &Role::MinterOperator(creator.clone()), &operator);
+    client.mint(&operator, &user, &content_id, &1_000, &true);
+
+    client.revoke_role(&creator, &Role::MinterOperator(creator.clone()), &operator);
+    assert!(!client.has_role(&Role::MinterOperator(creator.clone()), &operator));
+    assert_eq!(client.try_mint(&operator, &user, &content_id, &1_000, &true), Err(Ok(Error::Unauthorized)));
+
+    client.revoke_role(&admin, &Role::ComplianceOfficer, &officer);
+    assert_eq!(client.try_freeze_account(&officer, &user, &true), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_treasurer_withdraw() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (token, token_admin) = create_token(&env, &client);
+    let (content_id, creator) = create_content(&env, &client, 1_000);
+    let buyer = Address::generate(&env);
+    let treasurer = Address::generate(&env);
+    let vault = Address::generate(&env);
+
+    assert_eq!(client.try_set_platform_fee(&(MAX_PLATFORM_FEE_BPS + 1)), Err(Ok(Error::InvalidFee)));
+    client.set_platform_fee(&500);
+    assert_eq!(client.platform_fee(), 500);
+
+    // Sales leave the platform fee in the contract
+    token_admin.mint(&buyer, &1_000);
+    client.mint(&buyer, &buyer, &content_id, &1_000, &true);
+    assert_eq!(token.balance(&creator), 950);
+    assert_eq!(token.balance(&client.address), 50);
+
+    assert_eq!(client.try_withdraw(&treasurer, &token.address, &vault, &40), Err(Ok(Error::Unauthorized)));
+    client.grant_role(&admin, &Role::Treasurer, &treasurer);
+    assert_eq!(client.try_withdraw(&treasurer, &token.address, &vault, &0), Err(Ok(Error::InvalidPrice)));
+    client.withdraw(&treasurer, &token.address, &vault, &40);
+
+    assert_eq!(token.balance(&vault), 40);
+    assert_eq!(token.balance(&client.address), 10);
+}
+
+#[test]
+fn test_revoke_key() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let (token, token_admin) = create_token(&env, &client);
+    let user = Address::generate(&env);
+    let stranger = Address::generate(&env);
+
+    client.set_renewal_terms(&content_id, &1_000, &0);
+    let first = client.mint(&user, &user, &content_id, &1_000, &true);
+    let second = client.mint(&user, &user, &content_id, &1_000, &true);
+    client.list_key(&second, &400, &token.address);
+    token_admin.mint(&stranger, &400);
+    let renter = Address::generate(&env);
+    client.set_user(&first, &Some(renter.clone()), &500);
+    client.approve(&user, &stranger, &first, &1_000);
+    assert_eq!(client.balance(&user), 2);
+
+    assert_eq!(client.try_revoke_key(&stranger, &first, &1), Err(Ok(Error::Unauthorized)));
+
+    client.revoke_key(&creator, &first, &1);
+    let key = client.get_key(&first).unwrap();
+    assert_eq!(key.status, KeyStatus::Revoked);
+    assert_eq!(key.owner, user);
+    assert_eq!(key.user, None);
+    assert_eq!(client.key_status(&first), KeyStatus::Revoked);
+    assert_eq!(client.balance(&user), 1);
+    assert_eq!(client.owned_count(&user), 1);
+    assert_eq!(client.balance_of(&user, &content_id), 1);
+    assert_eq!(client.keys_of(&user, &0, &10), Vec::from_array(&env, [second]));
+    assert_eq!(client.total_keys(), 1);
+    assert_eq!(client.get_approved(&first), None);
+    assert_eq!(client.has_access(&renter, &content_id), None);
+    assert_eq!(client.has_access(&user, &content_id).unwrap().id, second);
+    assert_eq!(client.try_revoke_key(&creator, &first, &1), Err(Ok(Error::KeyRevoked)));
+    assert_eq!(client.try_burn(&first), Err(Ok(Error::KeyRevoked)));
+    assert_eq!(client.total_keys(), 1);
+    assert_eq!(client.try_renew(&first, &1), Err(Ok(Error::KeyRevoked)));
+    assert_eq!(client.try_transfer(&user, &stranger, &first), Err(Ok(Error::KeyRevoked)));
+
+    // Admin revocation voids listings
+    client.revoke_key(&admin, &second, &2);
+    assert_eq!(client.get_listing(&second), None);
+    assert_eq!(client.try_buy_listing(&stranger, &second), Err(Ok(Error::ListingNotFound)));
+    assert_eq!(client.has_access(&user, &content_id), None);
+    assert_eq!(client.balance(&user), 0);
+    assert_eq!(client.owned_count(&user), 0);
+    assert_eq!(client.total_keys(), 0);
+}
+
+#[test]
+fn test_key_status() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+
+    assert_eq!(client.try_key_status(&1), Err(Ok(Error::KeyNotFound)));
+
+    let key_id = client.mint(&user, &user, &content_id, &1_000, &true);
+    assert_eq!(client.key_status(&key_id), KeyStatus::Active);
+
+    client.freeze_account(&admin, &user, &true);
+    assert_eq!(client.key_status(&key_id), KeyStatus::Suspended);
+    client.freeze_account(&admin, &user, &false);
+
+    client.suspend_content(&creator, &content_id, &true);
+    assert_eq!(client.key_status(&key_id), KeyStatus::Suspended);
+    client.suspend_content(&creator, &content_id, &false);
+
+    // Expiry is reported before the stored status catches up
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert_eq!(client.key_status(&key_id), KeyStatus::Expired);
+    assert_eq!(client.get_key(&key_id).unwrap().status, KeyStatus::Active);
+    client.deactivate_expired_key(&key_id);
+    assert_eq!(client.get_key(&key_id).unwrap().status, KeyStatus::Expired);
+
+    client.revoke_key(&creator, &key_id, &0);
+    assert_eq!(client.key_status(&key_id), KeyStatus::Revoked);
+}
+
+#[contract]
+struct RedemptionRecorder;
+
+#[contractimpl]
+impl RedemptionRecorder {
+    pub fn on_redeem(env: Env, key_id: u64, owner: Address, _content_id: String) {
+        env.storage().instance().set(&key_id, &owner);
+    }
+
+    pub fn redeemed_by(env: Env, key_id: u64) -> Option<Address> {
+        env.storage().instance().get(&key_id)
+    }
+}
+
+#[test]
+fn test_burn() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+
+    let kept = client.mint(&user, &user, &content_id, &1_000, &true);
+    let burned = client.mint(&user, &user, &content_id, &1_000, &true);
+    assert_eq!(client.total_keys(), 2);
+
+    client.burn(&burned);
+    assert_eq!(client.key_status(&burned), KeyStatus::Burned);
+    assert_eq!(client.keys_of(&user, &0, &10), Vec::from_array(&env, [kept]));
+    assert_eq!(client.owned_count(&user), 1);
+    assert_eq!(client.balance_of(&user, &content_id), 1);
+    assert_eq!(client.total_keys(), 1);
+    assert_counters_consistent(&client, &[&user]);
+
+    // Burned keys are gone for good
+    assert_eq!(client.try_burn(&burned), Err(Ok(Error::KeyBurned)));
+    assert_eq!(client.try_transfer(&user, &creator, &burned), Err(Ok(Error::KeyBurned)));
+    assert_eq!(client.try_revoke_key(&creator, &burned, &0), Err(Ok(Error::KeyBurned)));
+    client.set_renewal_terms(&content_id, &1_000, &0);
+    assert_eq!(client.try_renew(&burned, &1), Err(Ok(Error::KeyBurned)));
+    client.bump_key(&burned);
+
+    // Burning does not free supply
+    assert_eq!(client.remaining_supply(&content_id), Some(98));
+}
+
+#[test]
+fn test_burn_to_redeem() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    let hook = env.register_contract(None, RedemptionRecorder);
+    let recorder = RedemptionRecorderClient::new(&env, &hook);
+
+    assert_eq!(client.redemption_hook(&content_id), None);
+    client.set_redemption_hook(&content_id, &Some(hook.clone()));
+    assert_eq!(client.redemption_hook(&content_id), Some(hook));
+
+    let valid = client.mint(&user, &user, &content_id, &1_000, &true);
+    let expired = client.mint(&user, &user, &content_id, &500, &true);
+    env.ledger().with_mut(|li| li.timestamp = 600);
+
+    client.burn(&valid);
+    assert_eq!(recorder.redeemed_by(&valid), Some(user.clone()));
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("burn"), user.clone()).into_val(&env));
+    let event: KeyBurnedEvent = data.into_val(&env);
+    assert!(event.redeemed);
+
+    // Expired keys can be cleaned up but are not redeemed
+    client.burn(&expired);
+    assert_eq!(recorder.redeemed_by(&expired), None);
+    assert_eq!(client.owned_count(&user), 0);
+
+    // A failing hook keeps the key in the owner's hands
+    let kept = client.mint(&user, &user, &content_id, &1_000, &true);
+    client.set_redemption_hook(&content_id, &Some(Address::generate(&env)));
+    assert_eq!(client.try_burn(&kept), Err(Ok(Error::RedemptionFailed)));
+    assert_eq!(client.key_status(&kept), KeyStatus::Active);
+    assert_eq!(client.owned_count(&user), 1);
+
+    client.set_redemption_hook(&content_id, &None);
+    assert_eq!(client.redemption_hook(&content_id), None);
+}
+
+#[test]
+fn test_rentals() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let (token, _) = create_token(&env, &client);
+    let owner = Address::generate(&env);
+    let renter = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let key_id = client.mint(&owner, &owner, &content_id, &1_000, &true);
+    let locked = client.mint(&owner, &owner, &content_id, &1_000, &false);
+
+    assert_eq!(client.try_set_user(&locked, &Some(renter.clone()), &500), Err(Ok(Error::NotTransferable)));
+    assert_eq!(client.try_set_user(&key_id, &Some(renter.clone()), &2_000), Err(Ok(Error::InvalidDuration)));
+
+    client.set_user(&key_id, &Some(renter.clone()), &500);
+    assert_eq!(client.user_of(&key_id), Some(renter.clone()));
+    assert_eq!(client.has_access(&renter, &content_id).unwrap().id, key_id);
+    assert_eq!(client.has_access(&owner, &content_id).unwrap().id, locked);
+
+    // The owner keeps the key but cannot move it while it is lent
+    assert_eq!(client.balance(&owner), 2);
+    assert_eq!(client.try_transfer(&owner, &buyer, &key_id), Err(Ok(Error::KeyRented)));
+    assert_eq!(client.try_list_key(&key_id, &100, &token.address), Err(Ok(Error::KeyRented)));
+    assert_eq!(client.try_burn(&key_id), Err(Ok(Error::KeyRented)));
+
+    // Cancelling the lease frees the key
+    client.set_user(&key_id, &None, &0);
+    assert_eq!(client.user_of(&key_id), None);
+    assert_eq!(client.has_access(&renter, &content_id), None);
+
+    // Leases run out on their own and are cleared on transfer
+    client.set_user(&key_id, &Some(renter.clone()), &500);
+    env.ledger().with_mut(|li| li.timestamp = 501);
+    assert_eq!(client.user_of(&key_id), None);
+    assert_eq!(client.has_access(&renter, &content_id), None);
+    client.transfer(&owner, &buyer, &key_id);
+    assert_eq!(client.get_key(&key_id).unwrap().user, None);
+    assert_eq!(client.has_access(&buyer, &content_id).unwrap().id, key_id);
+
+    // Suspension and freezes apply to renters too
+    client.set_user(&key_id, &Some(renter.clone()), &900);
+    client.freeze_account(&admin, &buyer, &true);
+    assert_eq!(client.key_status(&key_id), KeyStatus::Suspended);
+    assert_eq!(client.has_access(&renter, &content_id), None);
+    client.freeze_account(&admin, &buyer, &false);
+    assert_eq!(client.has_access(&renter, &content_id).unwrap().id, key_id);
+    client.suspend_content(&creator, &content_id, &true);
+    assert_eq!(client.has_access(&renter, &content_id), None);
+}
+
+#[test]
+fn test_approvals() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let owner = Address::generate(&env);
+    let spender = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let receiver = Address::generate(&env);
+
+    let key_id = client.mint(&owner, &owner, &content_id, &1_000, &true);
+    let other = client.mint(&owner, &owner, &content_id, &1_000, &true);
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    assert_eq!(client.try_transfer_from(&spender, &owner, &receiver, &key_id), Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.try_approve(&owner, &spender, &key_id, &99), Err(Ok(Error::InvalidExpiration)));
+
+    // Single key approvals expire and end when the key moves
+    client.approve(&owner, &spender, &key_id, &200);
+    assert_eq!(client.get_approved(&key_id), Some(spender.clone()));
+    assert_eq!(client.try_transfer_from(&spender, &owner, &receiver, &other), Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.try_transfer_from(&spender, &receiver, &owner, &key_id), Err(Ok(Error::Unauthorized)));
+
+    env.ledger().with_mut(|li| li.sequence_number = 201);
+    assert_eq!(client.get_approved(&key_id), None);
+    assert_eq!(client.try_transfer_from(&spender, &owner, &receiver, &key_id), Err(Ok(Error::Unauthorized)));
+
+    client.approve(&owner, &spender, &key_id, &300);
+    client.transfer_from(&spender, &owner, &receiver, &key_id);
+    assert_eq!(client.get_key(&key_id).unwrap().owner, receiver);
+    assert_eq!(client.get_approved(&key_id), None);
+
+    // Operators act for every key of the owner until disallowed
+    client.set_approval_for_all(&owner, &operator, &true);
+    assert!(client.is_approved_for_all(&owner, &operator));
+    client.transfer_from(&operator, &owner, &receiver, &other);
+    assert_eq!(client.get_key(&other).unwrap().owner, receiver);
+
+    client.set_approval_for_all(&owner, &operator, &false);
+    assert!(!client.is_approved_for_all(&owner, &operator));
+    assert_counters_consistent(&client, &[&owner, &receiver]);
+}
+
+#[test]
+fn test_nft_interface() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, _) = create_content(&env, &client, 0);
+    let owner = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let spender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+
+    assert_eq!(client.name(), String::from_str(&env, "Digital Access Keys"));
+    assert_eq!(client.symbol(), String::from_str(&env, "DAK"));
+    assert_eq!(client.try_owner_of(&1), Err(Ok(Error::KeyNotFound)));
+
+    let key_id = client.mint(&owner, &owner, &content_id, &1_000, &true);
+    assert_eq!(client.owner_of(&key_id), owner);
+    assert_eq!(client.balance(&owner), 1);
+    assert_eq!(client.token_uri(&key_id), String::from_str(&env, ""));
+
+    // Only the owner can transfer directly
+    assert_eq!(client.try_transfer(&receiver, &operator, &key_id), Err(Ok(Error::Unauthorized)));
+
+    // Operators may approve on the owner's behalf
+    assert_eq!(client.try_approve(&operator, &spender, &key_id, &100), Err(Ok(Error::Unauthorized)));
+    client.set_approval_for_all(&owner, &operator, &true);
+    client.approve(&operator, &spender, &key_id, &100);
+    client.transfer_from(&spender, &owner, &receiver, &key_id);
+    assert_eq!(client.owner_of(&key_id), receiver);
+
+    client.burn(&key_id);
+    assert_eq!(client.try_owner_of(&key_id), Err(Ok(Error::KeyBurned)));
+}
+
+#[test]
+fn test_token_uri() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+
+    let key_id = client.mint(&user, &user, &content_id, &1_000, &true);
+    assert_eq!(client.token_uri(&key_id), String::from_str(&env, ""));
+
+    let base_uri = String::from_str(&env, "https://keys.example/course-1/");
+    client.set_base_uri(&content_id, &base_uri);
+    assert_eq!(client.base_uri(&content_id), Some(base_uri));
+    assert_eq!(client.token_uri(&key_id), String::from_str(&env, "https://keys.example/course-1/1"));
+
+    let too_long = String::from_bytes(&env, &[b'a'; MAX_URI_LEN as usize + 1]);
+    assert_eq!(client.try_set_base_uri(&content_id, &too_long), Err(Ok(Error::InvalidUri)));
+
+    // Keys that no longer exist as tokens have no metadata
+    let revoked = client.mint(&user, &user, &content_id, &1_000, &true);
+    client.revoke_key(&creator, &revoked, &1);
+    assert_eq!(client.try_token_uri(&revoked), Err(Ok(Error::KeyRevoked)));
+    assert_eq!(client.try_owner_of(&revoked), Err(Ok(Error::KeyRevoked)));
+    client.burn(&key_id);
+    assert_eq!(client.try_token_uri(&key_id), Err(Ok(Error::KeyBurned)));
+
+    client.set_base_uri(&content_id, &String::from_str(&env, ""));
+    assert_eq!(client.base_uri(&content_id), None);
+}
+
+#[test]
+fn test_metadata_json() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+
+    client.update_content(
+        &content_id,
+        &String::from_str(&env, "Rust \"Basics\""),
+        &String::from_str(&env, "Line one\nLine two"),
+        &0,
+        &100,
+    );
+    let key_id = client.mint(&creator, &user, &content_id, &1_000, &false);
+    let key = client.get_key(&key_id).unwrap();
+    let content = client.get_content_metadata(&content_id).unwrap();
+
+    let mut json = std::string::String::new();
+    write_metadata_json(&mut json, &key, &content, "https://keys.example/course-1.png").unwrap();
+    assert_eq!(
+        json,
+        "{\"name\":\"Rust \\\"Basics\\\" #1\",\"description\":\"Line one\\nLine two\",\
+         \"image\":\"https://keys.example/course-1.png\",\"attributes\":[\
+         {\"trait_type\":\"Content\",\"value\":\"course-1\"},\
+         {\"display_type\":\"date\",\"trait_type\":\"Expires\",\"value\":1000},\
+         {\"trait_type\":\"Transferable\",\"value\":false},\
+         {\"trait_type\":\"Status\",\"value\":\"Active\"}]}"
+    );
+
+    let mut perpetual = key.clone();
+    perpetual.expires_at = NEVER_EXPIRES;
+    json.clear();
+    write_metadata_json(&mut json, &perpetual, &content, "").unwrap();
+    assert!(json.contains("{\"trait_type\":\"Expires\",\"value\":\"Never\"}"));
+}
+
+fn sign_voucher(
+    env: &Env,
+    client: &DigitalAccessKeysContractClient,
+    signer: &ed25519_dalek::SigningKey,
+    voucher: &Voucher,
+) -> BytesN<64> {
+    use ed25519_dalek::Signer;
+    use soroban_sdk::xdr::ToXdr;
+
+    let message = (client.address.clone(), voucher.clone()).to_xdr(env);
+    let mut bytes = [0u8; 1024];
+    let len = message.len() as usize;
+    message.copy_into_slice(&mut bytes[..len]);
+    BytesN::from_array(env, &signer.sign(&bytes[..len]).to_bytes())
+}
+
+#[test]
+fn test_mint_with_voucher() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 100);
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    let signer = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+
+    let voucher = Voucher {
+        content_id: content_id.clone(),
+        recipient: Some(user.clone()),
+        duration: 1_000,
+        transferable: false,
+        nonce: 1,
+        deadline: 500,
+    };
+    let signature = sign_voucher(&env, &client, &signer, &voucher);
+    assert_eq!(client.try_mint_with_voucher(&user, &voucher, &signature), Err(Ok(Error::VoucherKeyNotSet)));
+
+    let public_key = BytesN::from_array(&env, &signer.verifying_key().to_bytes());
+    client.set_voucher_key(&creator, &public_key);
+    assert_eq!(client.voucher_key(&creator), Some(public_key));
+
+    // Vouchers for a named recipient only work for them
+    assert_eq!(client.try_mint_with_voucher(&other, &voucher, &signature), Err(Ok(Error::Unauthorized)));
+
+    let key_id = client.mint_with_voucher(&user, &voucher, &signature);
+    let key = client.get_key(&key_id).unwrap();
+    assert_eq!(key.owner, user);
+    assert_eq!(key.expires_at, 1_000);
+    assert!(!key.transferable);
+    assert!(client.is_nonce_used(&creator, &1));
+
+    // Nonces cannot be replayed
+    assert_eq!(client.try_mint_with_voucher(&user, &voucher, &signature), Err(Ok(Error::NonceUsed)));
+
+    // Open vouchers can be redeemed by anyone before the deadline
+    let open = Voucher {
+        recipient: None,
+        nonce: 2,
+        ..voucher.clone()
+    };
+    let open_signature = sign_voucher(&env, &client, &signer, &open);
+    env.ledger().with_mut(|li| li.timestamp = 501);
+    assert_eq!(client.try_mint_with_voucher(&other, &open, &open_signature), Err(Ok(Error::VoucherExpired)));
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    client.mint_with_voucher(&other, &open, &open_signature);
+    assert_eq!(client.balance(&other), 1);
+
+    // Tampered vouchers fail verification
+    let tampered = Voucher {
+        nonce: 3,
+        duration: 100_000,
+        ..voucher.clone()
+    };
+    let tampered_signature = sign_voucher(&env, &client, &signer, &Voucher { nonce: 3, ..voucher });
+    assert!(client.try_mint_with_voucher(&user, &tampered, &tampered_signature).is_err());
+    assert!(!client.is_nonce_used(&creator, &3));
+}
+
+#[test]
+fn test_voucher_respects_admin_only_policy() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (content_id, creator) = create_content(&env, &client, 0);
+    let user = Address::generate(&env);
+    let signer = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+    client.set_voucher_key(&creator, &BytesN::from_array(&env, &signer.verifying_key().to_bytes()));
+
+    let voucher = Voucher {
+        content_id: content_id.clone(),
+        recipient: None,
+        duration: 1_000,
+        transferable: true,
+        nonce: 1,
+        deadline: 500,
+    };
+    let signature = sign_voucher(&env, &client, &signer, &voucher);
+
+    // Creators cannot issue admin-only keys, not even through vouchers
+    client.set_mint_policy(&content_id, &MintPolicy::AdminOnly);
+    assert_eq!(client.try_mint_with_voucher(&user, &voucher, &signature), Err(Ok(Error::Unauthorized)));
+    assert!(!client.is_nonce_used(&creator, &1));
+
+    client.set_mint_policy(&content_id, &MintPolicy::CreatorOnly);
+    client.mint_with_voucher(&user, &voucher, &signature);
+    assert_eq!(client.balance(&user), 1);
+}
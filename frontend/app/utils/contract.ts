@@ -6,6 +6,7 @@ import {
   BASE_FEE,
   xdr,
   Address,
+  nativeToScVal,
 } from "@stellar/stellar-sdk";
 import freighterApi from "@stellar/freighter-api";
 
@@ -40,21 +41,55 @@ const RPC_URL = "https://soroban-testnet.stellar.org";
 const server = new SorobanRpc.Server(RPC_URL);
 
 // Contract interface
+export type KeyStatus =
+  | "Active"
+  | "Expired"
+  | "Revoked"
+  | "Suspended"
+  | "Burned"
+  | "PendingActivation";
+
 export interface AccessKey {
   id: bigint;
   owner: string;
   content_id: string;
   expires_at: bigint;
-  is_active: boolean;
+  status: KeyStatus;
   transferable: boolean;
+  user?: string;
+  user_expires: bigint;
 }
 
+export type MintPolicy =
+  | "CreatorOnly"
+  | "PublicSale"
+  | "Allowlist"
+  | "AdminOnly";
+
+export type DurationPolicy =
+  | { tag: "Fixed"; values: [bigint] }
+  | { tag: "Bounded"; values: [bigint, bigint] }
+  | { tag: "Perpetual"; values: void };
+
 export interface ContentMetadata {
   title: string;
   description: string;
   creator: string;
   price: bigint;
   max_keys: number;
+  mint_policy: MintPolicy;
+  duration_policy: DurationPolicy;
+  retired: boolean;
+  royalty_bps: number;
+  renewal_period: bigint;
+  renewal_price: bigint;
+}
+
+function durationPolicyToScVal(policy: DurationPolicy): xdr.ScVal {
+  const values = policy.tag === "Perpetual"
+    ? []
+    : policy.values.map(seconds => xdr.ScVal.scvU64(xdr.Uint64.fromString(seconds.toString())));
+  return xdr.ScVal.scvVec([xdr.ScVal.scvSymbol(policy.tag), ...values]);
 }
 
 // Contract interaction class
@@ -118,16 +153,18 @@ export class DigitalAccessKeysContract {
 
   // Contract methods
   async mint(
+    minter: string,
     to: string,
     contentId: string,
-    expiresAt: number,
+    duration: number,
     transferable: boolean
   ): Promise<string> {
     const operation = this.contract.call(
       "mint",
+      new Address(minter).toScVal(),
       new Address(to).toScVal(),
       xdr.ScVal.scvString(contentId),
-      xdr.ScVal.scvU64(xdr.Uint64.fromString(expiresAt.toString())),
+      xdr.ScVal.scvU64(xdr.Uint64.fromString(duration.toString())),
       xdr.ScVal.scvBool(transferable)
     );
 
@@ -135,10 +172,12 @@ export class DigitalAccessKeysContract {
   }
 
   async transfer(keyId: number, to: string): Promise<string> {
+    const from = await this.getUserPublicKey();
     const operation = this.contract.call(
       "transfer",
-      xdr.ScVal.scvU64(xdr.Uint64.fromString(keyId.toString())),
-      new Address(to).toScVal()
+      new Address(from).toScVal(),
+      new Address(to).toScVal(),
+      xdr.ScVal.scvU64(xdr.Uint64.fromString(keyId.toString()))
     );
 
     return await this.buildAndSubmitTransaction(operation);
@@ -245,7 +284,7 @@ if (isSimulateTransactionSuccessResponse(result)) {
     return null;
   }
 }
- async getUserKeys(user: string): Promise<number[]> {
+ async getUserKeys(user: string, cursor = 0, limit = 100): Promise<number[]> {
   try {
     const result = await server.simulateTransaction(
       new TransactionBuilder(
@@ -257,8 +296,10 @@ if (isSimulateTransactionSuccessResponse(result)) {
       )
         .addOperation(
           this.contract.call(
-            "get_user_keys",
-            new Address(user).toScVal()
+            "keys_of",
+            new Address(user).toScVal(),
+            xdr.ScVal.scvU32(cursor),
+            xdr.ScVal.scvU32(limit)
           )
         )
         .setTimeout(300)
@@ -310,21 +351,42 @@ async isKeyValid(keyId: number): Promise<boolean> {
   }
 }
 
-  async setContentMetadata(
+  async registerContent(
     contentId: string,
     title: string,
     description: string,
     creator: string,
-    price: number,
-    maxKeys: number
+    price: bigint,
+    maxKeys: number,
+    durationPolicy: DurationPolicy
   ): Promise<string> {
     const operation = this.contract.call(
-      "set_content_metadata",
+      "register_content",
       xdr.ScVal.scvString(contentId),
       xdr.ScVal.scvString(title),
       xdr.ScVal.scvString(description),
       new Address(creator).toScVal(),
-      xdr.ScVal.scvI64(xdr.Int64.fromString(price.toString())),
+      nativeToScVal(price, { type: "i128" }),
+      xdr.ScVal.scvU32(maxKeys),
+      durationPolicyToScVal(durationPolicy)
+    );
+
+    return await this.buildAndSubmitTransaction(operation);
+  }
+
+  async updateContent(
+    contentId: string,
+    title: string,
+    description: string,
+    price: bigint,
+    maxKeys: number
+  ): Promise<string> {
+    const operation = this.contract.call(
+      "update_content",
+      xdr.ScVal.scvString(contentId),
+      xdr.ScVal.scvString(title),
+      xdr.ScVal.scvString(description),
+      nativeToScVal(price, { type: "i128" }),
       xdr.ScVal.scvU32(maxKeys)
     );
 